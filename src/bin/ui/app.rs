@@ -1,4 +1,4 @@
-use std::io::{Error, ErrorKind};
+use std::io::Error;
 use std::time::Duration;
 use std::time::Instant;
 use crossterm::event;
@@ -6,7 +6,7 @@ use crossterm::event::{Event, KeyEventKind};
 use crossterm::event::KeyCode;
 use ratatui::backend::Backend;
 use ratatui::Terminal;
-use lr2::{Transition, Turing, TuringError};
+use lr2::{Outcome, RulesetError, Tape, Transition, Turing, TuringError, Verdict};
 use crate::window::Window;
 
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
@@ -21,63 +21,67 @@ pub (crate) struct App {
     frame_timeout: Duration,
     step_last: Instant,
     speed: u8,
-    pub(crate) history: History,
+    history: History,
     max_iteration: usize,
+    outcome: Option<Outcome>,
+    paused: bool,
 }
 
 pub(crate) struct History {
-    storage: Vec<Transition>,
+    storage: lr2::History,
     offset: usize,
     follow: bool,
-    listeners: Vec<Box<dyn FnMut(&Transition, usize)>>,
 }
 
 impl History {
-    pub(crate) fn new(storage: Vec<Transition>, offset: usize, follow: bool) -> History {
+    pub(crate) fn new(storage: lr2::History, offset: usize, follow: bool) -> History {
         History {
             storage,
             offset,
             follow,
-            listeners: vec![],
         }
     }
-    pub(crate) fn add_listener(&mut self, listener: impl FnMut(&Transition, usize) + 'static) {
-        self.listeners.push(Box::new(listener));
-    }
 
-    pub(crate) fn add(&mut self, transition: Transition) {
-        self.storage.push(transition.clone());
-        self.notify(&transition);
-    }
-    pub(crate) fn notify(&mut self, x: &Transition) {
-        for listener in &mut self.listeners {
-            listener(x, self.storage.len());
-        }
+    /// adds the transition, `tapes` are the tapes after it
+    pub(crate) fn add(&mut self, transition: Transition, tapes: &[Tape]) {
+        self.storage.push(transition, tapes);
     }
 }
 
 
 impl App {
-    pub(crate) fn new(turing: Turing) -> App {
+    pub(crate) fn new(mut turing: Turing) -> App {
+        turing.detect_loops(true);
         App {
-            history: History::new(vec![], 0, true),
+            history: History::new(lr2::History::new(turing.tapes().clone()), 0, true),
             state: AppState::Running,
             turing,
             frame_timeout: Duration::from_millis(250),
             step_last: Instant::now(),
             speed: 4,
             max_iteration: 1_000,
+            outcome: None,
+            paused: false,
         }
     }
+    /// runs the machine without the ui up to the first breakpoint, the observers of the machine see every step
     pub (crate) fn run(&mut self) -> std::io::Result<()> {
-        while self.is_running() {
-            self.next_step().map_err(|e| Error::new(ErrorKind::Other, e))?;
+        let run = self.turing.run(self.max_iteration).map_err(Error::other)?;
+        self.state = AppState::Quit;
+        match run.outcome().clone() {
+            // without a verdict a missing rule is an error of the machine
+            Outcome::MissingRule { state, symbols } => {
+                self.stop_on_error(TuringError::RuleNotFound { rule_error: RulesetError::RuleNotFound { state, symbols } }).map_err(Error::other)
+            }
+            outcome => {
+                self.outcome = Some(outcome);
+                Ok(())
+            }
         }
-        Ok(())
     }
     pub (crate) fn run_ui(&mut self, mut terminal: Terminal<impl Backend>) -> std::io::Result<()> {
         while self.is_running() {
-            self.update().map_err(|e| Error::new(ErrorKind::Other, e))?;
+            self.update().map_err(Error::other)?;
             self.handle_events()?;
             self.draw(&mut terminal)?;
         }
@@ -85,7 +89,7 @@ impl App {
     }
 
     fn update(&mut self) -> Result<(), TuringError> {
-        if self.step_last.elapsed() > self.frame_timeout * self.speed as u32{
+        if !self.paused && self.step_last.elapsed() > self.frame_timeout * self.speed as u32{
             self.step_last = Instant::now();
             return self.next_step();
         }
@@ -93,26 +97,54 @@ impl App {
     }
 
     fn next_step(&mut self) -> Result<(), TuringError> {
-        self.turing.next_transition().map(|transition| {
-            self.turing.apply_transition(&transition);
-            if self.history.storage.len() >= self.max_iteration || transition.rule().mov().is_terminal() {
-                self.state = AppState::Quit;
+        match self.turing.step() {
+            Ok(transition) => {
+                self.history.add(transition, self.turing.tapes());
+                self.outcome = self.outcome_after_step();
+                if self.outcome.is_none() && self.history.storage.len() >= self.max_iteration {
+                    self.outcome = Some(Outcome::StepLimitReached);
+                }
+                if self.outcome.is_some() {
+                    self.state = AppState::Quit;
+                }
+                // the ui waits for the user to continue
+                if self.turing.hit_breakpoint().is_some() {
+                    self.paused = true;
+                }
+                Ok(())
+            }
+            Err(e) => self.stop_on_error(e),
+        }
+    }
+
+    fn outcome_after_step(&self) -> Option<Outcome> {
+        if let Some((first_seen, repeated_at)) = self.turing.detected_loop() {
+            Some(Outcome::LoopDetected { first_seen, repeated_at })
+        } else if self.turing.is_halted() {
+            Some(Outcome::Halted)
+        } else {
+            None
+        }
+    }
+
+    fn stop_on_error(&mut self, e: TuringError) -> Result<(), TuringError> {
+        self.state = AppState::Quit;
+        match e {
+            // a machine with a verdict rejects the input when there is no rule
+            TuringError::RuleNotFound { rule_error: RulesetError::RuleNotFound { state, symbols } } if self.turing.verdict().is_some() => {
+                self.outcome = Some(Outcome::MissingRule { state, symbols });
+                Ok(())
             }
-            self.history.add(transition);
-        }).map_err(|e| {
-            self.state = AppState::Quit;
-            e
-        })
+            e => Err(e),
+        }
     }
     fn draw(&self, terminal: &mut Terminal<impl Backend>) -> std::io::Result<()> {
         let window = Window::new(
             &self.history.storage,
             self.history.offset,
             self.history.follow,
-            self.turing.tape(),
-            self.turing.ruleset(),
-            self.turing.state(),
-            self.turing.tape().read()
+            &self.turing,
+            self.paused,
         );
         terminal.draw(|frame| frame.render_widget(window, frame.size()))?;
         Ok(())
@@ -127,6 +159,7 @@ impl App {
             match event::read()? {
                 Event::Key(key) if key.kind == KeyEventKind::Press => match key.code {
                     Char('q') | Esc => self.quit(),
+                    Char(' ') => self.paused = !self.paused,
                     Down => self.scroll_down(),
                     Up => self.scroll_up(),
                     _ => (),
@@ -157,7 +190,21 @@ impl App {
     fn quit(&mut self) {
         self.state = AppState::Quit;
     }
-    pub(crate) fn history(&self) -> &Vec<Transition> {
+    /// how the machine stopped, `None` while it is running or when the user quit
+    pub(crate) fn outcome(&self) -> Option<&Outcome> {
+        self.outcome.as_ref()
+    }
+    /// number of steps made by the machine
+    pub(crate) fn steps(&self) -> usize {
+        self.turing.steps()
+    }
+    pub(crate) fn verdict(&self) -> Option<Verdict> {
+        self.turing.verdict()
+    }
+    pub(crate) fn turing(&self) -> &Turing {
+        &self.turing
+    }
+    pub(crate) fn history(&self) -> &lr2::History {
         &self.history.storage
     }
 }
\ No newline at end of file
@@ -4,21 +4,22 @@ use ratatui::layout::{Layout, Rect};
 use ratatui::layout::Constraint::{Length, Min};
 use ratatui::prelude::*;
 use ratatui::widgets::{Block, Widget, Paragraph, Borders, ScrollbarState, Scrollbar, ScrollbarOrientation};
-use lr2::{Transition};
 use crate::tape::TapeWidget;
 
 pub(crate) struct History<'a> {
-    history: &'a Vec<Transition>,
+    history: &'a lr2::History,
     height: u16,
     follow: bool,
     scroll_offset: usize,
 }
 
 impl<'a> History<'a> {
-    pub(crate) fn new(history: &'a Vec<Transition>, scroll_offset: usize, scroll_follow: bool) -> Self {
+    pub(crate) fn new(history: &'a lr2::History, scroll_offset: usize, scroll_follow: bool) -> Self {
+        // every tape takes two rows and a gap
+        let tapes = history.transitions().first().map_or(1, |t| t.deltas().len()) as u16;
         History {
             history,
-            height: 5,
+            height: 2 + 3 * tapes,
             follow: scroll_follow,
             scroll_offset,
         }
@@ -34,6 +35,10 @@ impl Widget for History<'_> {
             self.scroll_offset
         };
         let records_on_the_screen = min(viewport_capacity, self.history.len() - scroll_offset);
+        // only the tapes of the first record are rebuilt, the next ones are made by its transitions
+        let Some(mut tapes) = self.history.tapes(scroll_offset) else {
+            return;
+        };
         let mut scroll_state = ScrollbarState::default()
             .content_length(self.history.len())
             .position(scroll_offset)
@@ -41,16 +46,22 @@ impl Widget for History<'_> {
         Layout::vertical(vec![Length(self.height); records_on_the_screen])
             .split(area)
             .iter()
-            .zip(self.history.iter().skip(scroll_offset))
+            .zip(self.history.transitions().iter().skip(scroll_offset))
             .enumerate()
             .for_each(|(i, (a, trans))| {
                 let block = Block::default()
                     .title(format!("Step {}", i + scroll_offset + 1).italic())
                     .borders(Borders::ALL);
                 let inner = block.inner(*a);
-                let [rect_tape, rect_rule] = Layout::horizontal([Min(15), Length(5)]).areas(inner);
-                TapeWidget::new(trans.tape()).render(rect_tape, buf);
-                Paragraph::new(trans.rule().to_string()).render(rect_rule, buf);
+                let rule = trans.rule().to_string();
+                let [rect_tapes, rect_rule] = Layout::horizontal([Min(15), Length(rule.len().max(5) as u16)]).areas(inner);
+                Layout::vertical(vec![Length(3); tapes.len()])
+                    .split(rect_tapes)
+                    .iter()
+                    .zip(tapes.iter())
+                    .for_each(|(rect_tape, tape)| TapeWidget::new(tape).render(*rect_tape, buf));
+                trans.apply(&mut tapes);
+                Paragraph::new(rule).render(rect_rule, buf);
                 block.render(*a, buf);
             });
         Scrollbar::default()
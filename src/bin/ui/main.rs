@@ -16,66 +16,334 @@ use crossterm::{
 use ratatui::prelude::{CrosstermBackend, Terminal};
 use std::io::{stdout, Result, Error, ErrorKind, Write};
 use std::path::Path;
+use std::process;
 use app::App;
-use lr2::{Ruleset, Tape, Transition, Turing};
+use lr2::{Breakpoint, Exploration, Observer, Outcome, Ruleset, Snapshot, Tape, Transition, Turing, Verdict, JflapMachine, MorphettMachine, RuleState, YamlMachine};
+use std::cell::RefCell;
+use std::rc::Rc;
 use std::str::FromStr;
-use clap::Parser;
+use clap::{Parser, Subcommand, ValueEnum};
 
 #[derive(Parser, Debug)]
-#[command(version, about, long_about = None)]
+#[command(version, about, long_about = None, args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+    /// required unless the rules keep the tape, like the turingmachine.io YAML
     #[arg(short, long)]
-    tape: String,
-    #[arg(short, long)]
-    rules: String,
+    tape: Option<String>,
+    /// required to resume a snapshot saved without the ruleset
+    #[arg(short, long, required_unless_present = "resume")]
+    rules: Option<String>,
+    /// format of the rules, by default it's chosen by the file extension and the Markdown table otherwise
+    #[arg(long, value_enum)]
+    format: Option<Format>,
     #[arg(short, long)]
     out: Option<String>,
     #[arg(long = "no-interactive", default_value = "false")]
     no_interactive: bool,
+    /// explore every branch of a nondeterministic machine and print the accepting one
+    #[arg(long, default_value = "false")]
+    nondeterministic: bool,
+    #[arg(long = "max-depth", default_value = "1000")]
+    max_depth: usize,
+    #[arg(long = "max-configurations", default_value = "100000")]
+    max_configurations: usize,
+    /// pause when the condition holds: state=7, symbol=+, state=7,symbol=+, head=-3 or tape=1+1
+    #[arg(short, long = "break")]
+    breakpoints: Vec<Breakpoint>,
+    /// print how many times every rule fired, state visits and head movement after the run
+    #[arg(long, default_value = "false")]
+    stats: bool,
+    /// continue the machine saved by `--save` instead of starting it on the tape
+    #[arg(long)]
+    resume: Option<String>,
+    /// save the machine with its ruleset when the run ends or the user quits
+    #[arg(long)]
+    save: Option<String>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// compile a program into the Markdown table of its ruleset
+    Compile {
+        program: String,
+        #[arg(short, long)]
+        out: Option<String>,
+    },
+    /// convert a machine between the formats chosen by the file extensions: the Markdown table (.md),
+    /// the turingmachine.io YAML (.yaml, .yml), JFLAP (.jff) and the rules one per line (.tm)
+    Convert {
+        from: String,
+        to: String,
+        /// format of `from`, instead of the one chosen by the extension
+        #[arg(long, value_enum)]
+        format: Option<Format>,
+        /// tape file of the formats which keep no tape: read when converting from them, written when converting to them
+        #[arg(short, long)]
+        tape: Option<String>,
+    },
+    /// report the problems of the rules found without running them: unreachable states, unknown states and symbols,
+    /// missing rules and states which never halt. Exits with an error when there are any
+    Check {
+        rules: String,
+        /// format of the rules, instead of the one chosen by the extension
+        #[arg(long, value_enum)]
+        format: Option<Format>,
+    },
+}
+
+/// formats of the machines
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum Format {
+    /// the Markdown table
+    Markdown,
+    /// turingmachine.io
+    Yaml,
+    /// JFLAP
+    Jflap,
+    /// `<state> <read> <write> <direction> <next state>` lines of the simulator of Anthony Morphett
+    Morphett,
+}
+
+impl Format {
+    /// the format of the file extension
+    fn of(path: &str) -> Option<Format> {
+        match Path::new(path).extension().and_then(|extension| extension.to_str()) {
+            Some("md") => Some(Format::Markdown),
+            Some("yaml" | "yml") => Some(Format::Yaml),
+            Some("jff") => Some(Format::Jflap),
+            Some("tm") => Some(Format::Morphett),
+            _ => None,
+        }
+    }
+
+    fn of_file(path: &str) -> Result<Format> {
+        Format::of(path).ok_or_else(|| Error::new(ErrorKind::InvalidInput, format!("Unknown machine format: {}", path)))
+    }
+
+    /// the format keeps the tape with the machine
+    fn has_tape(&self) -> bool {
+        matches!(self, Format::Yaml)
+    }
+}
+
+/// every tape is described by two lines: the tape content and the start position of the head.
+/// The first line may declare the blank symbol of the tapes, like `blank: λ`, otherwise the blank of the ruleset is used.
+fn string_to_tapes(s: String, blank: char) -> Result<Vec<Tape>> {
+    let mut lines = s.trim_end().lines().peekable();
+    let blank = match lines.next_if(|line| line.starts_with("blank:")) {
+        Some(line) => {
+            let mut chars = line["blank:".len()..].trim().chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => c,
+                _ => return Err(Error::new(ErrorKind::InvalidInput, format!("Invalid blank symbol: {}", line))),
+            }
+        }
+        None => blank,
+    };
+    let mut tapes = Vec::new();
+    while let Some(tape_str) = lines.next() {
+        let start: isize = lines.next().ok_or_else(|| Error::other("Start position doesnt found"))?.parse().map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
+        tapes.push(Tape::with_blank(tape_str.chars().collect(), start, 0, blank));
+    }
+    if tapes.is_empty() {
+        return Err(Error::other("Tape doesnt found"));
+    }
+    Ok(tapes)
 }
 
-fn string_to_tape(s: String) -> Result<Tape> {
-    let mut lines = s.lines();
-    let tape_str = lines.next().ok_or_else(|| Error::new(ErrorKind::Other, "Tape doesnt found"))?;
-    let start: isize = lines.next().ok_or_else(|| Error::new(ErrorKind::Other, "Start position doesnt found"))?.parse().map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
-    Ok(Tape::new(tape_str.chars().collect(), start, 0))
+/// writes the tapes in the format read by `string_to_tapes`, from the first to the last non-blank cell or the head
+fn tapes_to_string(tapes: &[Tape], blank: char) -> String {
+    let mut s = String::new();
+    if let Some(tape) = tapes.first().filter(|tape| tape.blank() != blank) {
+        s.push_str(&format!("blank: {}\n", tape.blank()));
+    }
+    for tape in tapes {
+        let (first, last) = tape.content_bounds();
+        let cells: String = (first..=last).map(|position| tape.get(position)).collect();
+        s.push_str(&format!("{}\n{}\n", cells, tape.head() - first));
+    }
+    s
 }
 
 fn main() -> Result<()> {
     let args = Args::parse();
-    let tape_str = fs::read_to_string(args.tape.clone())?;
-    let rules_str = fs::read_to_string(args.rules.clone())?;
+    if let Some(command) = args.command {
+        return run_command(command);
+    }
     let out = open_output(args.out)?;
-
-    let tape = string_to_tape(tape_str)?;
-    let rules = Ruleset::from_str(rules_str.as_str()).map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
-    let mt = Turing::new(tape, 0, rules);
-    if args.no_interactive {
-        non_interactive(mt, out)
+    let machine = match &args.rules {
+        Some(rules) => Some(read_machine(rules, args.format.or(Format::of(rules)).unwrap_or(Format::Markdown))?),
+        None => None,
+    };
+    let mut mt = match (&args.resume, &args.tape, machine) {
+        (Some(resume), _, machine) => {
+            let snapshot = Snapshot::from_str(fs::read_to_string(resume)?.as_str()).map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
+            snapshot.resume(machine.map(|(_, rules, _)| rules)).map_err(|e| Error::new(ErrorKind::InvalidInput, e))?
+        }
+        (None, tape, Some((state, rules, embedded))) => {
+            let tapes = match (tape, embedded) {
+                (Some(tape), _) => string_to_tapes(fs::read_to_string(tape)?, rules.blank())?,
+                (None, Some(tapes)) => tapes,
+                (None, None) => return Err(Error::new(ErrorKind::InvalidInput, "Tape is required, the rules keep no tape")),
+            };
+            if tapes.len() != rules.tapes() {
+                return Err(Error::new(ErrorKind::InvalidInput, format!("Ruleset is written for {} tapes, but {} tapes given", rules.tapes(), tapes.len())));
+            }
+            Turing::with_tapes(tapes, state, rules)
+        }
+        _ => return Err(Error::new(ErrorKind::InvalidInput, "Tape and rules are required")),
+    };
+    args.breakpoints.into_iter().for_each(|b| mt.add_breakpoint(b));
+    mt.collect_statistics(args.stats);
+    if args.nondeterministic {
+        return nondeterministic(mt, args.max_depth, args.max_configurations, out);
+    }
+    let app = if args.no_interactive {
+        non_interactive(mt, out)?
     } else {
-        interactive(mt, out)
+        interactive(mt, out)?
+    };
+    if let Some(save) = args.save {
+        fs::write(save, app.turing().snapshot(true).to_string())?;
     }
+    Ok(())
 }
 
+fn run_command(command: Command) -> Result<()> {
+    match command {
+        Command::Compile { program, out } => {
+            let rules = Ruleset::compile(fs::read_to_string(program)?.as_str()).map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
+            open_output(out)?.write_all(rules.to_string().as_bytes())
+        }
+        Command::Convert { from, to, format, tape } => convert(from, to, format, tape),
+        Command::Check { rules, format } => check(rules, format),
+    }
+}
 
-fn interactive(turing: Turing, mut out: Box<dyn Write>) -> Result<()> {
+/// reads the initial state and the ruleset of the machine, with its tape when the format keeps it
+fn read_machine(path: &str, format: Format) -> Result<(RuleState, Ruleset, Option<Vec<Tape>>)> {
+    let text = fs::read_to_string(path)?;
+    Ok(match format {
+        Format::Markdown => {
+            let rules = Ruleset::from_str(&text).map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
+            (rules.initial_state(), rules, None)
+        }
+        Format::Yaml => {
+            let machine = YamlMachine::from_str(&text).map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
+            (machine.state(), machine.ruleset().clone(), Some(vec![machine.tape().clone()]))
+        }
+        Format::Jflap => {
+            let machine = JflapMachine::from_str(&text).map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
+            (machine.state(), machine.ruleset().clone(), None)
+        }
+        Format::Morphett => {
+            let machine = MorphettMachine::from_str(&text).map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
+            (machine.state(), machine.ruleset().clone(), None)
+        }
+    })
+}
+
+/// the machine is read and written with its tape, which is kept in the `tape` file for the formats without one
+fn convert(from: String, to: String, format: Option<Format>, tape: Option<String>) -> Result<()> {
+    let source = match format {
+        Some(format) => format,
+        None => Format::of_file(&from)?,
+    };
+    let target = Format::of_file(&to)?;
+    let (state, rules, tapes) = read_machine(&from, source)?;
+    let tapes = match (tapes, &tape) {
+        (Some(tapes), _) => tapes,
+        (None, Some(tape)) => string_to_tapes(fs::read_to_string(tape)?, rules.blank())?,
+        (None, None) => vec![Tape::with_blank(vec![], 0, 0, rules.blank()); rules.tapes()],
+    };
+    if tapes.len() != rules.tapes() {
+        return Err(Error::new(ErrorKind::InvalidInput, format!("Ruleset is written for {} tapes, but {} tapes given", rules.tapes(), tapes.len())));
+    }
+    if let Some(tape) = tape.filter(|_| source.has_tape() && !target.has_tape()) {
+        fs::write(tape, tapes_to_string(&tapes, rules.blank()))?;
+    }
+    let text = match target {
+        Format::Markdown => rules.to_string(),
+        Format::Yaml => {
+            let tape = tapes.into_iter().next().expect("a machine has a tape");
+            YamlMachine::new(rules, state, tape).map_err(|e| Error::new(ErrorKind::InvalidInput, e))?.to_string()
+        }
+        Format::Jflap => JflapMachine::new(rules, state).to_string(),
+        Format::Morphett => MorphettMachine::new(rules, state).map_err(|e| Error::new(ErrorKind::InvalidInput, e))?.to_string(),
+    };
+    fs::write(to, text)
+}
+
+/// prints the problems of the ruleset, and exits with status 1 when there are any
+fn check(rules: String, format: Option<Format>) -> Result<()> {
+    let (_, rules, _) = read_machine(&rules, format.or(Format::of(&rules)).unwrap_or(Format::Markdown))?;
+    let lints = rules.check();
+    let mut out = stdout();
+    for lint in &lints {
+        writeln!(out, "{}", lint)?;
+    }
+    if !lints.is_empty() {
+        writeln!(out, "Problems found: {}", lints.len())?;
+        out.flush()?;
+        process::exit(1);
+    }
+    writeln!(out, "No problems found")
+}
+
+fn interactive(turing: Turing, mut out: Box<dyn Write>) -> Result<App> {
     stdout().execute(EnterAlternateScreen)?;
     enable_raw_mode()?;
     let terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
     let mut app = App::new(turing);
     app.run_ui(terminal)?;
     restore_terminal()?;
-    write_history(app, out.as_mut())?;
-    Ok(())
+    write_history(&app, out.as_mut())?;
+    write_outcome(&app, out.as_mut())?;
+    write_statistics(&app, out.as_mut())?;
+    Ok(app)
 }
 
-fn non_interactive(turing: Turing, mut out: Box<dyn Write>) -> Result<()> {
+fn non_interactive(mut turing: Turing, out: Box<dyn Write>) -> Result<App> {
+    let trace = Rc::new(RefCell::new(TraceWriter { out, error: None }));
+    turing.add_observer(Box::new(trace.clone()));
     let mut app = App::new(turing);
-    app.history.add_listener(move |t, i| {
-        write_transition(t, i, out.as_mut()).unwrap();
-    });
     app.run()?;
-    Ok(())
+    let mut trace = trace.borrow_mut();
+    if let Some(e) = trace.error.take() {
+        return Err(e);
+    }
+    write_outcome(&app, trace.out.as_mut())?;
+    write_statistics(&app, trace.out.as_mut())?;
+    Ok(app)
+}
+
+/// writes every step of the machine as soon as it is made
+struct TraceWriter {
+    out: Box<dyn Write>,
+    // observers can't fail, so the first write error is kept until the end of the run
+    error: Option<Error>,
+}
+
+impl Observer for TraceWriter {
+    // the tapes are written before they are changed by the step
+    fn before_step(&mut self, turing: &Turing, transition: &Transition) {
+        if self.error.is_none() {
+            self.error = write_transition(transition, turing.tapes(), turing.steps() + 1, self.out.as_mut()).err();
+        }
+    }
+}
+
+fn nondeterministic(turing: Turing, max_depth: usize, max_configurations: usize, mut out: Box<dyn Write>) -> Result<()> {
+    match turing.explore(max_depth, max_configurations) {
+        Exploration::Accepted(path) => {
+            write_path(&path, turing.tapes().clone(), out.as_mut())?;
+            out.write_all(b"Accepted\n")
+        }
+        Exploration::Rejected => out.write_all(b"Rejected: no branch halts\n"),
+        Exploration::LimitReached => out.write_all(b"Unknown: exploration limit reached\n"),
+    }
 }
 
 fn restore_terminal() -> Result<()> {
@@ -88,31 +356,67 @@ fn open_output(out: Option<String>) -> Result<Box<dyn Write>> {
     Ok(match out {
         Some(x) => {
             let path = Path::new(&x);
-            Box::new(File::create(&path)?) as Box<dyn Write>
+            Box::new(File::create(path)?) as Box<dyn Write>
         }
         None => Box::new(stdout()) as Box<dyn Write>,
     })
 }
 
-fn write_history(app: App, file: &mut dyn Write) -> Result<()> {
-    app.history().iter().enumerate().try_for_each(|(i, t)| write_transition(t, i, file))?;
+fn write_history(app: &App, file: &mut dyn Write) -> Result<()> {
+    let tapes = app.history().tapes(0).expect("the history starts with the initial tapes");
+    write_path(app.history().transitions(), tapes, file)
+}
+
+/// writes the transitions made one after another from the tapes
+fn write_path(transitions: &[Transition], mut tapes: Vec<Tape>, file: &mut dyn Write) -> Result<()> {
+    for (i, transition) in transitions.iter().enumerate() {
+        write_transition(transition, &tapes, i, file)?;
+        transition.apply(&mut tapes);
+    }
+    Ok(())
+}
+
+fn write_outcome(app: &App, file: &mut dyn Write) -> Result<()> {
+    let verdict = match app.verdict() {
+        Some(Verdict::Accept) => ": accepted",
+        Some(Verdict::Reject) => ": rejected",
+        None => "",
+    };
+    match app.outcome() {
+        Some(Outcome::Halted) => writeln!(file, "Halted after {} steps{}", app.steps(), verdict),
+        Some(Outcome::StepLimitReached) => writeln!(file, "Stopped after {} steps: step limit reached", app.steps()),
+        Some(Outcome::LoopDetected { first_seen, repeated_at }) => writeln!(file, "Loops forever from step {} with period {}", first_seen, repeated_at - first_seen),
+        Some(Outcome::MissingRule { state, symbols }) => writeln!(file, "No rule for state {} and symbol {} after {} steps{}", state, symbols.iter().collect::<String>(), app.steps(), verdict),
+        Some(Outcome::BreakpointHit { breakpoint }) => writeln!(file, "Stopped after {} steps at breakpoint {}", app.steps(), breakpoint),
+        None => Ok(()),
+    }
+}
+
+fn write_statistics(app: &App, file: &mut dyn Write) -> Result<()> {
+    if let Some(statistics) = app.turing().statistics() {
+        write!(file, "{}", statistics)?;
+    }
     Ok(())
 }
 
-fn write_transition(transition: &Transition, step: usize, file: &mut dyn Write) -> Result<()> {
+/// `tapes` are the tapes before the transition
+fn write_transition(transition: &Transition, tapes: &[Tape], step: usize, file: &mut dyn Write) -> Result<()> {
+    let tapes = match tapes {
+        [tape] => format!("Tape:\t{:#}\n", tape),
+        _ => tapes.iter().enumerate().map(|(i, tape)| format!("Tape {}:\t{:#}\n", i + 1, tape)).collect(),
+    };
     file.write_all(format!(
         "\
 =============== Step: {} ===============
-Tape:\t{}
-State:\t\t{}\tReplace:\t{}
+{}State:\t\t{}\tReplace:\t{}
 Next state:\t{}\tMove:\t\t{}
 ",
         step,
-        transition.tape().to_string(),
-        transition.state().to_string(),
-        transition.rule().write(),
+        tapes,
+        transition.state(),
+        transition.rule().writes().iter().collect::<String>(),
         transition.rule().next_state(),
-        transition.rule().mov(),
+        transition.rule().moves().iter().map(|m| m.to_string()).collect::<String>(),
     ).as_ref())?;
     Ok(())
 }
\ No newline at end of file
@@ -6,17 +6,19 @@ use lr2::{Ruleset, RuleState};
 pub(crate) struct RulesetWidget<'a> {
     ruleset: &'a Ruleset,
     state: RuleState,
-    symbol: char,
+    symbol: Vec<char>,
 }
 
 impl<'a> RulesetWidget<'a> {
-    pub(crate) fn new(ruleset: &'a Ruleset, rule: RuleState, symbol: char) -> Self {
+    pub(crate) fn new(ruleset: &'a Ruleset, rule: RuleState, symbol: Vec<char>) -> Self {
         RulesetWidget { ruleset, state: rule, symbol }
     }
 }
 
 impl Widget for RulesetWidget<'_> {
     fn render(self, area: ratatui::layout::Rect, buf: &mut ratatui::buffer::Buffer) {
+        // the rules may come from a row with wildcards
+        let current_row = self.ruleset.matching_row(&self.state, &self.symbol).unwrap_or(&self.symbol);
         let rows = vec![Row::new(
             vec![Cell::from("".to_string())]
                 .into_iter()
@@ -30,10 +32,10 @@ impl Widget for RulesetWidget<'_> {
             .into_iter()
             .chain(
                 self.ruleset
-                    .alphabet()
-                    .into_iter()
+                    .symbols()
+                    .iter()
                     .map(|symbol| {
-                        vec![match (Cell::from(symbol.to_string()).light_cyan(), *symbol == self.symbol) {
+                        vec![match (Cell::from(symbol.iter().collect::<String>()).light_cyan(), symbol == current_row) {
                             (cell, true) => cell.on_dark_gray(),
                             (cell, false) => cell,
                         }]
@@ -42,7 +44,7 @@ impl Widget for RulesetWidget<'_> {
                                 .states()
                                 .iter()
                                 .map(|state|
-                                match (Cell::from(self.ruleset.find(state, symbol).unwrap().to_string()), *state == self.state, *symbol == self.symbol) {
+                                match (Cell::from(self.ruleset.cell(state, symbol).iter().map(|r| r.to_string()).collect::<Vec<_>>().join(" ")), *state == self.state, symbol == current_row) {
                                     (cell, false, true) | (cell, true, false) => cell.on_dark_gray(),
                                     (cell, true, true)=> cell.on_blue(),
                                     (cell, false, false) => cell,
@@ -52,6 +54,9 @@ impl Widget for RulesetWidget<'_> {
                     }).map(|row| Row::new(row).on_gray())
             );
         let cols_count = self.ruleset.states().len() + 1;
-        Table::new(rows, vec![Length(5); cols_count]).render(area, buf);
+        // a cell holds a write and a move for every tape and the name of the next state
+        let longest_state = self.ruleset.states().iter().map(|s| s.name().chars().count()).max().unwrap_or(0).max(2);
+        let col_width = (2 * self.ruleset.tapes() + longest_state + 1) as u16;
+        Table::new(rows, vec![Length(col_width); cols_count]).render(area, buf);
     }
 }
\ No newline at end of file
@@ -19,10 +19,10 @@ impl Widget for TapeWidget<'_> {
         const MIN_SYMBOLS: usize = 3;
         let symbols = ((area.width / WIDTH) as usize).max(MIN_SYMBOLS);
         let index = self.tape.index();
-        let vec = self.tape.data();
         let min = index.saturating_sub(symbols);
-        let max = index.saturating_add(symbols).min(vec.len());
-        let show_data = &vec[min..max];
+        let max = index.saturating_add(symbols).min(self.tape.data_len());
+        // only the visible cells are read, the data of the tape may be huge
+        let show_data = (min..max).map(|i| self.tape.get(self.tape.head_offset() + i as isize)).collect::<Vec<char>>();
         let local_index = index - min;
 
         let rows = [
@@ -36,8 +36,7 @@ impl Widget for TapeWidget<'_> {
             ),
             Row::new(show_data
                          .iter()
-                         .enumerate()
-                         .map(|(_ ,c)| Cell::from(c.to_string()))
+                         .map(|c| if *c == self.tape.blank() { Cell::from(c.to_string()).dim() } else { Cell::from(c.to_string()) })
                          .enumerate()
                          .map(|(i, c)| if i == local_index { c.on_cyan() } else { c })
                 .collect::<Vec<Cell>>()
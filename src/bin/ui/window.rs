@@ -4,29 +4,31 @@ use ratatui::layout::Constraint::{Fill, Length, Min};
 use ratatui::prelude::Widget;
 use crate::history::History;
 use ratatui::widgets::Block;
-use lr2::{Ruleset, RuleState, Tape, Transition};
+use lr2::{Breakpoint, Turing};
 use crate::ruleset::RulesetWidget;
 use crate::tape::TapeWidget;
 
 pub (crate) struct Window<'a> {
-    tape: TapeWidget<'a>,
+    tapes: Vec<TapeWidget<'a>>,
     history: History<'a>,
     ruleset: RulesetWidget<'a>,
+    paused: bool,
+    breakpoint: Option<&'a Breakpoint>,
 }
 impl<'a> Window<'a> {
     pub (crate) fn new(
-        history: &'a Vec<Transition>,
+        history: &'a lr2::History,
         scroll_offset: usize,
         scroll_follow: bool,
-        tape: &'a Tape,
-        ruleset: &'a Ruleset,
-        state: RuleState,
-        symbol: char,
+        turing: &'a Turing,
+        paused: bool,
     ) -> Self {
         Window {
-            tape: TapeWidget::new(tape),
+            tapes: turing.tapes().iter().map(TapeWidget::new).collect(),
             history: History::new(history, scroll_offset, scroll_follow),
-            ruleset: RulesetWidget::new(ruleset, state, symbol),
+            ruleset: RulesetWidget::new(turing.ruleset(), turing.state(), turing.read()),
+            paused,
+            breakpoint: turing.hit_breakpoint(),
         }
     }
 }
@@ -35,12 +37,22 @@ impl Widget for Window<'_> {
     fn render(self, area: Rect, buf: &mut Buffer) {
         let layout = Layout::horizontal([Min(80), Length(80)]);
         let [left, right] = layout.areas(area);
-        let [tape_rect, ruleset_rect] = Layout::vertical([Length(4), Fill(1)]).areas(left);
+        let [tapes_rect, ruleset_rect] = Layout::vertical([Length(4 * self.tapes.len() as u16), Fill(1)]).areas(left);
 
-        let tape_block = Block::default().title("Tape").borders(ratatui::widgets::Borders::ALL);
-        self.tape.render(tape_block.inner(tape_rect), buf);
-        tape_block.render(tape_rect, buf);
-        let ruleset_block = Block::default().title("Rules").borders(ratatui::widgets::Borders::ALL);
+        let tapes_count = self.tapes.len();
+        let tape_rects = Layout::vertical(vec![Length(4); tapes_count]).split(tapes_rect);
+        for (i, (tape, tape_rect)) in self.tapes.into_iter().zip(tape_rects.iter()).enumerate() {
+            let title = if tapes_count == 1 { "Tape".to_string() } else { format!("Tape {}", i + 1) };
+            let tape_block = Block::default().title(title).borders(ratatui::widgets::Borders::ALL);
+            tape.render(tape_block.inner(*tape_rect), buf);
+            tape_block.render(*tape_rect, buf);
+        }
+        let title = match (self.paused, self.breakpoint) {
+            (true, Some(breakpoint)) => format!("Rules [breakpoint {}, press space to continue]", breakpoint),
+            (true, None) => "Rules [paused, press space to continue]".to_string(),
+            (false, _) => "Rules".to_string(),
+        };
+        let ruleset_block = Block::default().title(title).borders(ratatui::widgets::Borders::ALL);
         self.ruleset.render(ruleset_block.inner(ruleset_rect), buf);
         ruleset_block.render(ruleset_rect, buf);
         let history_block = Block::default().title("History").borders(ratatui::widgets::Borders::ALL);
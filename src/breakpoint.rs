@@ -0,0 +1,148 @@
+use std::error::Error;
+use std::fmt;
+use std::fmt::Display;
+use std::str::FromStr;
+use crate::rule::RuleState;
+use crate::tape::Tape;
+
+/// A condition on the configuration of the machine. `Turing` checks its breakpoints after every step.
+/// Symbol, head and tape conditions of a multi-tape machine match when any of the tapes matches.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Breakpoint {
+    State(RuleState),
+    Symbol(char),
+    StateSymbol(RuleState, char),
+    Head(isize),
+    Tape(String),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BreakpointParseError {
+    InvalidFormat { format: String },
+    InvalidState { state: String },
+    InvalidSymbol { symbol: String },
+    InvalidHead { head: String },
+}
+
+impl Breakpoint {
+    pub fn matches(&self, state: &RuleState, tapes: &[Tape]) -> bool {
+        match self {
+            Breakpoint::State(s) => s == state,
+            Breakpoint::Symbol(c) => tapes.iter().any(|t| t.read() == *c),
+            Breakpoint::StateSymbol(s, c) => s == state && tapes.iter().any(|t| t.read() == *c),
+            Breakpoint::Head(head) => tapes.iter().any(|t| t.head() == *head),
+            Breakpoint::Tape(pattern) => {
+                let pattern: Vec<char> = pattern.chars().collect();
+                tapes.iter().any(|t| t.contains(&pattern))
+            }
+        }
+    }
+}
+
+impl Display for Breakpoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Breakpoint::State(state) => write!(f, "state={}", state),
+            Breakpoint::Symbol(symbol) => write!(f, "symbol={}", symbol),
+            Breakpoint::StateSymbol(state, symbol) => write!(f, "state={},symbol={}", state, symbol),
+            Breakpoint::Head(head) => write!(f, "head={}", head),
+            Breakpoint::Tape(pattern) => write!(f, "tape={}", pattern),
+        }
+    }
+}
+
+impl FromStr for Breakpoint {
+    type Err = BreakpointParseError;
+
+    /// format is one of `state=7`, `symbol=+`, `state=7,symbol=+`, `head=-3`, `tape=1+1`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut state = None;
+        let mut symbol = None;
+        let mut condition = None;
+        for part in s.split(',') {
+            let (key, value) = part.split_once('=').ok_or_else(|| BreakpointParseError::InvalidFormat { format: s.to_string() })?;
+            match key.trim() {
+                "state" => state = Some(value.trim().parse().map_err(|_| BreakpointParseError::InvalidState { state: value.to_string() })?),
+                "symbol" => {
+                    let mut chars = value.chars();
+                    symbol = match (chars.next(), chars.next()) {
+                        (Some(c), None) => Some(c),
+                        _ => return Err(BreakpointParseError::InvalidSymbol { symbol: value.to_string() }),
+                    }
+                }
+                "head" => condition = Some(Breakpoint::Head(value.trim().parse().map_err(|_| BreakpointParseError::InvalidHead { head: value.to_string() })?)),
+                "tape" if !value.is_empty() => condition = Some(Breakpoint::Tape(value.to_string())),
+                _ => return Err(BreakpointParseError::InvalidFormat { format: s.to_string() }),
+            }
+        }
+        match (state, symbol, condition) {
+            (Some(state), None, None) => Ok(Breakpoint::State(state)),
+            (None, Some(symbol), None) => Ok(Breakpoint::Symbol(symbol)),
+            (Some(state), Some(symbol), None) => Ok(Breakpoint::StateSymbol(state, symbol)),
+            (None, None, Some(condition)) => Ok(condition),
+            _ => Err(BreakpointParseError::InvalidFormat { format: s.to_string() }),
+        }
+    }
+}
+
+impl Display for BreakpointParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BreakpointParseError::InvalidFormat { format } => write!(f, "Invalid breakpoint: {}", format),
+            BreakpointParseError::InvalidState { state } => write!(f, "Invalid state: {}", state),
+            BreakpointParseError::InvalidSymbol { symbol } => write!(f, "Invalid symbol: {}", symbol),
+            BreakpointParseError::InvalidHead { head } => write!(f, "Invalid head position: {}", head),
+        }
+    }
+}
+
+impl Error for BreakpointParseError {}
+
+#[cfg(test)]
+mod test {
+    use crate::breakpoint::{Breakpoint, BreakpointParseError};
+    use crate::rule::Move;
+    use crate::tape::Tape;
+
+    #[test]
+    fn test_breakpoint_from_str() {
+        assert_eq!("state=7".parse::<Breakpoint>().unwrap(), Breakpoint::State(7.into()));
+        assert_eq!("symbol=+".parse::<Breakpoint>().unwrap(), Breakpoint::Symbol('+'));
+        assert_eq!("state=7,symbol=+".parse::<Breakpoint>().unwrap(), Breakpoint::StateSymbol(7.into(), '+'));
+        assert_eq!("head=-3".parse::<Breakpoint>().unwrap(), Breakpoint::Head(-3));
+        assert_eq!("tape=1+1".parse::<Breakpoint>().unwrap(), Breakpoint::Tape("1+1".to_string()));
+        assert_eq!("state=a-b".parse::<Breakpoint>().unwrap_err(), BreakpointParseError::InvalidState { state: "a-b".to_string() });
+        assert_eq!("symbol=ab".parse::<Breakpoint>().unwrap_err(), BreakpointParseError::InvalidSymbol { symbol: "ab".to_string() });
+        assert_eq!("head=1,state=2".parse::<Breakpoint>().unwrap_err(), BreakpointParseError::InvalidFormat { format: "head=1,state=2".to_string() });
+        assert_eq!("7".parse::<Breakpoint>().unwrap_err(), BreakpointParseError::InvalidFormat { format: "7".to_string() });
+        for breakpoint in ["state=7", "state=carry", "symbol=+", "state=7,symbol=+", "head=-3", "tape=1+1"] {
+            assert_eq!(breakpoint.parse::<Breakpoint>().unwrap().to_string(), breakpoint);
+        }
+    }
+
+    #[test]
+    fn test_breakpoint_matches() {
+        let tapes = [Tape::new("12+3".chars().collect(), 2, 0)];
+        assert!(Breakpoint::State(7.into()).matches(&7.into(), &tapes));
+        assert!(!Breakpoint::State(7.into()).matches(&6.into(), &tapes));
+        assert!(Breakpoint::Symbol('+').matches(&0.into(), &tapes));
+        assert!(Breakpoint::StateSymbol(7.into(), '+').matches(&7.into(), &tapes));
+        assert!(!Breakpoint::StateSymbol(7.into(), '3').matches(&7.into(), &tapes));
+        assert!(Breakpoint::Head(2).matches(&0.into(), &tapes));
+        assert!(Breakpoint::Tape("2+3".to_string()).matches(&0.into(), &tapes));
+        assert!(!Breakpoint::Tape("3+2".to_string()).matches(&0.into(), &tapes));
+
+        let mut tape = Tape::new("12+3".chars().collect(), 0, 0);
+        tape.set_head(1_000_000_000);
+        tape.apply('4', &Move::Left);
+        let tapes = [tape];
+        assert!(Breakpoint::Tape("2+3".to_string()).matches(&0.into(), &tapes));
+        assert!(Breakpoint::Tape("3___".to_string()).matches(&0.into(), &tapes));
+        assert!(Breakpoint::Tape("__4".to_string()).matches(&0.into(), &tapes));
+        assert!(Breakpoint::Tape("_".repeat(1000)).matches(&0.into(), &tapes));
+        assert!(!Breakpoint::Tape("3_4".to_string()).matches(&0.into(), &tapes));
+        assert!(!Breakpoint::Tape("4_".to_string()).matches(&0.into(), &tapes));
+    }
+}
@@ -0,0 +1,357 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::error::Error;
+use std::fmt;
+use std::fmt::Display;
+use crate::rule::{Move, Rule, RuleState, WILDCARD};
+use crate::ruleset::Ruleset;
+
+const KEYWORDS: [&str; 10] = ["move", "left", "right", "until", "write", "if", "goto", "halt", "accept", "reject"];
+
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CompileError {
+    InvalidStatement { line: usize, statement: String },
+    InvalidLabel { line: usize, label: String },
+    DuplicateLabel { line: usize, label: String },
+    UnknownLabel { line: usize, label: String },
+    /// the statements jump to each other forever without moving the head
+    EndlessJump { line: usize },
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Statement {
+    Move(Move),
+    MoveUntil(Move, Vec<char>),
+    Write(char),
+    If(Vec<char>, usize),
+    Goto(usize),
+    Halt(Stop),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Stop {
+    Halt,
+    Accept,
+    Reject,
+}
+
+/// what a statement does with the symbol under the head, `None` is any symbol the program doesnt mention
+enum Effect {
+    /// goes on to the statement without moving the head, the symbol may be replaced
+    Stay { write: Option<char>, next: usize },
+    Go { mov: Move, next: usize },
+    Stop(Stop),
+}
+
+/// A parsed program: the statements with their line numbers and the names of the labelled ones.
+/// The last statement is the `halt` after the end of the program.
+struct Program {
+    statements: Vec<(usize, Statement)>,
+    labels: HashMap<usize, String>,
+}
+
+impl Ruleset {
+    /// compiles a program of a single-tape machine into its ruleset. Every line holds a statement, optionally
+    /// after a label like `carry:`, and everything after a `;` is a comment:
+    /// ```text
+    /// ; adds one to a binary number, the head starts on its first digit
+    ///     move right until _
+    ///     move left
+    /// carry:
+    ///     if 1 goto one
+    ///     write 1
+    ///     halt
+    /// one:
+    ///     write 0
+    ///     move left
+    ///     goto carry
+    /// ```
+    /// The statements are `move left`, `move right`, `move right until a b` which stops on the first `a` or `b`,
+    /// `write a`, `if a b goto label`, `goto label`, `halt`, and `accept` and `reject` for recognizers.
+    /// The program halts after its last statement.
+    ///
+    /// A state is made only for the statements the head moves to, the other statements are merged into the rules
+    /// of those states, so `write 1` followed by `move left` is a single `1<` rule. Labels name the states, the other
+    /// states are numbered from 0, and the rules for the symbols the program doesnt mention are in the `*` row.
+    pub fn compile(program: &str) -> Result<Ruleset, CompileError> {
+        Program::parse(program)?.compile()
+    }
+}
+
+impl Program {
+    fn parse(program: &str) -> Result<Program, CompileError> {
+        // the statements refer to the labels by names until every label is known
+        let mut statements: Vec<(usize, Vec<&str>)> = Vec::new();
+        let mut labels: HashMap<&str, usize> = HashMap::new();
+        let mut names = HashMap::new();
+        for (index, line) in program.lines().enumerate() {
+            let line_number = index + 1;
+            let mut tokens: Vec<&str> = line.split(';').next().unwrap_or("").split_whitespace().collect();
+            if let Some(label) = tokens.first().and_then(|t| t.strip_suffix(':')) {
+                let invalid = label.parse::<RuleState>().is_err() || label.chars().all(|c| c.is_ascii_digit()) || KEYWORDS.contains(&label);
+                if invalid {
+                    return Err(CompileError::InvalidLabel { line: line_number, label: label.to_string() });
+                }
+                if labels.insert(label, statements.len()).is_some() {
+                    return Err(CompileError::DuplicateLabel { line: line_number, label: label.to_string() });
+                }
+                names.entry(statements.len()).or_insert_with(|| label.to_string());
+                tokens.remove(0);
+            }
+            if !tokens.is_empty() {
+                statements.push((line_number, tokens));
+            }
+        }
+        let mut parsed = Vec::new();
+        for (line, tokens) in &statements {
+            let invalid = || CompileError::InvalidStatement { line: *line, statement: tokens.join(" ") };
+            let target = |label: &str| labels.get(label).copied().ok_or_else(|| CompileError::UnknownLabel { line: *line, label: label.to_string() });
+            let statement = match tokens.as_slice() {
+                ["move", direction] => Statement::Move(parse_direction(direction).ok_or_else(invalid)?),
+                ["move", direction, "until", symbols @ ..] if !symbols.is_empty() => {
+                    Statement::MoveUntil(parse_direction(direction).ok_or_else(invalid)?, parse_symbols(symbols).ok_or_else(invalid)?)
+                }
+                ["write", symbol] => Statement::Write(parse_symbol(symbol).ok_or_else(invalid)?),
+                ["if", symbols @ .., "goto", label] if !symbols.is_empty() => Statement::If(parse_symbols(symbols).ok_or_else(invalid)?, target(label)?),
+                ["goto", label] => Statement::Goto(target(label)?),
+                ["halt"] => Statement::Halt(Stop::Halt),
+                ["accept"] => Statement::Halt(Stop::Accept),
+                ["reject"] => Statement::Halt(Stop::Reject),
+                _ => return Err(invalid()),
+            };
+            parsed.push((*line, statement));
+        }
+        parsed.push((program.lines().count() + 1, Statement::Halt(Stop::Halt)));
+        Ok(Program { statements: parsed, labels: names })
+    }
+
+    fn compile(&self) -> Result<Ruleset, CompileError> {
+        let mut symbols: Vec<char> = Vec::new();
+        for (_, statement) in &self.statements {
+            let mentioned = match statement {
+                Statement::MoveUntil(_, s) | Statement::If(s, _) => s.clone(),
+                Statement::Write(symbol) => vec![*symbol],
+                _ => vec![],
+            };
+            for symbol in mentioned {
+                if !symbols.contains(&symbol) {
+                    symbols.push(symbol);
+                }
+            }
+        }
+        let rows: Vec<Option<char>> = symbols.iter().copied().map(Some).chain([None]).collect();
+
+        let mut names: HashMap<usize, RuleState> = HashMap::new();
+        let mut numbered = 0;
+        let mut name = |statement: usize, queue: &mut VecDeque<usize>| -> RuleState {
+            names.entry(statement).or_insert_with(|| {
+                queue.push_back(statement);
+                match self.labels.get(&statement) {
+                    Some(label) => label.parse().expect("labels are valid state names"),
+                    None => {
+                        numbered += 1;
+                        (numbered - 1).into()
+                    }
+                }
+            }).clone()
+        };
+        let mut queue = VecDeque::new();
+        name(self.jump(0)?, &mut queue);
+        let mut rules = HashMap::new();
+        let mut states = Vec::new();
+        let mut stops = HashSet::new();
+        while let Some(statement) = queue.pop_front() {
+            let state = name(statement, &mut queue);
+            let mut cells = HashMap::new();
+            for row in &rows {
+                let (write, effect) = self.resolve(statement, *row)?;
+                let write = write.or(*row).unwrap_or(WILDCARD);
+                let rule = match effect {
+                    Effect::Go { mov, next } => Rule::new(write, mov, name(self.jump(next)?, &mut queue)),
+                    Effect::Stop(stop) => {
+                        stops.insert(stop);
+                        match stop {
+                            Stop::Halt => Rule::new(write, Move::Stop, state.clone()),
+                            Stop::Accept => Rule::new(write, Move::Stop, "accept".parse::<RuleState>().unwrap()),
+                            Stop::Reject => Rule::new(write, Move::Stop, "reject".parse::<RuleState>().unwrap()),
+                        }
+                    }
+                    Effect::Stay { .. } => unreachable!("resolve follows the statements until the head moves or stops"),
+                };
+                cells.insert(vec![row.unwrap_or(WILDCARD)], vec![rule]);
+            }
+            rules.insert(state.clone(), cells);
+            states.push(state);
+        }
+        let mut ruleset = Ruleset::new(rules, rows.iter().map(|row| vec![row.unwrap_or(WILDCARD)]).collect(), states);
+        if stops.contains(&Stop::Accept) {
+            ruleset.set_accept_states(vec!["accept".parse().unwrap()]);
+        }
+        if stops.contains(&Stop::Reject) {
+            ruleset.set_reject_states(vec!["reject".parse().unwrap()]);
+        }
+        Ok(ruleset)
+    }
+
+    fn effect(&self, statement: usize, symbol: Option<char>) -> Effect {
+        let next = statement + 1;
+        match &self.statements[statement].1 {
+            Statement::Move(mov) => Effect::Go { mov: *mov, next },
+            Statement::MoveUntil(mov, symbols) => match symbol {
+                Some(symbol) if symbols.contains(&symbol) => Effect::Stay { write: None, next },
+                _ => Effect::Go { mov: *mov, next: statement },
+            },
+            Statement::Write(symbol) => Effect::Stay { write: Some(*symbol), next },
+            Statement::If(symbols, target) => match symbol {
+                Some(symbol) if symbols.contains(&symbol) => Effect::Stay { write: None, next: *target },
+                _ => Effect::Stay { write: None, next },
+            },
+            Statement::Goto(target) => Effect::Stay { write: None, next: *target },
+            Statement::Halt(stop) => Effect::Stop(*stop),
+        }
+    }
+
+    /// follows the statements from `statement` on the symbol until the head moves or the program stops,
+    /// returns the last symbol written on the way
+    fn resolve(&self, mut statement: usize, mut symbol: Option<char>) -> Result<(Option<char>, Effect), CompileError> {
+        let mut written = None;
+        let mut visited = HashSet::new();
+        loop {
+            if !visited.insert((statement, symbol)) {
+                return Err(CompileError::EndlessJump { line: self.statements[statement].0 });
+            }
+            match self.effect(statement, symbol) {
+                Effect::Stay { write, next } => {
+                    if write.is_some() {
+                        written = write;
+                        symbol = write;
+                    }
+                    statement = next;
+                }
+                effect => return Ok((written, effect)),
+            }
+        }
+    }
+
+    /// the statement the jumps from `statement` lead to, so a `goto` doesnt get a state of its own
+    fn jump(&self, mut statement: usize) -> Result<usize, CompileError> {
+        let mut visited = HashSet::new();
+        while let Statement::Goto(target) = self.statements[statement].1 {
+            if !visited.insert(statement) {
+                return Err(CompileError::EndlessJump { line: self.statements[statement].0 });
+            }
+            statement = target;
+        }
+        Ok(statement)
+    }
+}
+
+fn parse_direction(direction: &str) -> Option<Move> {
+    match direction {
+        "left" => Some(Move::Left),
+        "right" => Some(Move::Right),
+        _ => None,
+    }
+}
+
+/// a symbol is a single character, the wildcard is not a symbol
+fn parse_symbol(symbol: &str) -> Option<char> {
+    let mut chars = symbol.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) if c != WILDCARD => Some(c),
+        _ => None,
+    }
+}
+
+fn parse_symbols(symbols: &[&str]) -> Option<Vec<char>> {
+    symbols.iter().map(|s| parse_symbol(s)).collect()
+}
+
+impl Display for CompileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CompileError::InvalidStatement { line, statement } => write!(f, "Invalid statement in line {}: {}", line, statement),
+            CompileError::InvalidLabel { line, label } => write!(f, "Invalid label in line {}: {}", line, label),
+            CompileError::DuplicateLabel { line, label } => write!(f, "Duplicate label in line {}: {}", line, label),
+            CompileError::UnknownLabel { line, label } => write!(f, "Unknown label in line {}: {}", line, label),
+            CompileError::EndlessJump { line } => write!(f, "Endless jump without moving the head in line {}", line),
+        }
+    }
+}
+
+impl Error for CompileError {}
+
+#[cfg(test)]
+mod test {
+    use crate::compiler::CompileError;
+    use crate::ruleset::Ruleset;
+    use crate::tape::Tape;
+    use crate::turing::Turing;
+
+    const INCREMENT: &str = "\
+; adds one to a binary number, the head starts on its first digit
+    move right until _
+    move left
+carry:
+    if 1 goto one
+    write 1
+    halt
+one:
+    write 0
+    move left
+    goto carry";
+
+    fn run(rules: Ruleset, tape: &str) -> String {
+        let mut turing = Turing::new(Tape::new(tape.chars().collect(), 0, 0), rules.initial_state(), rules);
+        turing.run(1000).unwrap();
+        assert!(turing.is_halted());
+        turing.tape().data().iter().collect::<String>().trim_matches('_').to_string()
+    }
+
+    #[test]
+    fn test_compile_increment() {
+        let rules = Ruleset::compile(INCREMENT).unwrap();
+        assert_eq!(rules.to_string(), "\
+|   | 0 | carry|
+|:-:|:-:|:-:|
+| _ | _<carry | 1!carry|
+| 1 | 1>0 | 0<carry|
+| 0 | 0>0 | 1!carry|
+| * | *>0 | 1!carry|
+");
+        assert_eq!(rules.to_string().parse::<Ruleset>().unwrap(), rules);
+        assert_eq!(run(rules.clone(), "1011"), "1100");
+        assert_eq!(run(rules, "111"), "1000");
+    }
+
+    #[test]
+    fn test_compile_recognizer() {
+        // accepts the words of "a" and "b" which end with "b"
+        let rules = Ruleset::compile("\
+start: move right until _
+       move left
+       if b goto yes
+       reject
+yes:   accept").unwrap();
+        assert_eq!(rules.accept_states(), &vec!["accept".parse().unwrap()]);
+        assert_eq!(rules.reject_states(), &vec!["reject".parse().unwrap()]);
+        let mut turing = Turing::new(Tape::new("aab".chars().collect(), 0, 0), rules.initial_state(), rules.clone());
+        turing.run(100).unwrap();
+        assert_eq!(turing.state().to_string(), "accept");
+        let mut turing = Turing::new(Tape::new("aba".chars().collect(), 0, 0), rules.initial_state(), rules);
+        turing.run(100).unwrap();
+        assert_eq!(turing.state().to_string(), "reject");
+    }
+
+    #[test]
+    fn test_compile_errors() {
+        let error = |program: &str| Ruleset::compile(program).err().unwrap();
+        assert_eq!(error("move up"), CompileError::InvalidStatement { line: 1, statement: "move up".to_string() });
+        assert_eq!(error("write ab"), CompileError::InvalidStatement { line: 1, statement: "write ab".to_string() });
+        assert_eq!(error("\ngoto nowhere"), CompileError::UnknownLabel { line: 2, label: "nowhere".to_string() });
+        assert_eq!(error("a: halt\na: halt"), CompileError::DuplicateLabel { line: 2, label: "a".to_string() });
+        assert_eq!(error("12: halt"), CompileError::InvalidLabel { line: 1, label: "12".to_string() });
+        assert_eq!(error("halt: halt"), CompileError::InvalidLabel { line: 1, label: "halt".to_string() });
+        assert_eq!(error("a: if x goto b\nb: goto a"), CompileError::EndlessJump { line: 1 });
+    }
+}
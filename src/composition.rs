@@ -0,0 +1,338 @@
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+use std::fmt::Display;
+use crate::rule::{Move, Rule, RuleState, WILDCARD};
+use crate::ruleset::{Ruleset, RulesetError};
+
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CompositionError {
+    /// the machines are written for different numbers of tapes
+    TapesMismatch { expected: usize, found: usize },
+    /// the machines use different blank symbols
+    BlankMismatch { expected: char, found: char },
+    /// the state of the call is already used by the caller
+    StateInUse { state: RuleState },
+    /// the return state of the call is not a state of the caller
+    UnknownState { state: RuleState },
+    /// the machine to run next or to call can't be started
+    InvalidRuleset { error: RulesetError },
+}
+
+/// Rules of the composed machine, filled by the parts one after another.
+struct Table {
+    rules: HashMap<RuleState, HashMap<Vec<char>, Vec<Rule>>>,
+    symbols: Vec<Vec<char>>,
+    states: Vec<RuleState>,
+    accept_states: Vec<RuleState>,
+    reject_states: Vec<RuleState>,
+    tapes: usize,
+}
+
+impl Ruleset {
+    /// runs this machine and then `next` on the tapes it leaves. Halting here, by a rule which stops every head
+    /// or by entering an accept state, continues in the initial state of `next`. Reject states still reject.
+    ///
+    /// The states of `next` are renamed to keep them apart from these ones: numeric states are shifted past the largest
+    /// numeric state of this machine, the other names get a suffix like `_1` when taken.
+    /// A halting rule is routed through an extra state, which moves the heads back, so the handover takes one more step.
+    pub fn then(&self, next: &Ruleset) -> Result<Ruleset, CompositionError> {
+        check_compatible(self, next)?;
+        check_runnable(next)?;
+        let mut taken: HashSet<RuleState> = all_states(self).into_iter().collect();
+        let offset = taken.iter().filter_map(RuleState::number).max().map_or(0, |n| n + 1);
+        let mut names = HashMap::new();
+        for state in all_states(next) {
+            let name = state.number().map_or_else(|| state.name().to_string(), |n| (n + offset).to_string());
+            names.insert(state, unique(name, &mut taken));
+        }
+        let mut table = Table::new(self.tapes());
+        table.add(self, &HashMap::new(), Some(&names[&next.initial_state()]), &mut taken);
+        table.add(next, &names, None, &mut taken);
+        Ok(table.build(self.blank()))
+    }
+
+    /// replaces `state` with a copy of `submachine`: entering `state` starts the submachine,
+    /// and its halt, by a rule which stops every head or by entering an accept state, continues in `return_state`.
+    /// Reject states of the submachine reject the whole machine.
+    ///
+    /// The initial state of the copy is named `state`, the other ones `{state}_{name}`, like `inc_1`, so a machine
+    /// may call the same submachine from several states. Rules of the caller which stop every head on their way into `state`
+    /// would halt the caller, so they and the halting rules of the submachine are routed through an extra state,
+    /// which moves the heads back.
+    pub fn call(&self, state: &RuleState, submachine: &Ruleset, return_state: &RuleState) -> Result<Ruleset, CompositionError> {
+        check_compatible(self, submachine)?;
+        check_runnable(submachine)?;
+        if self.states().contains(state) || self.is_halting_state(state) {
+            return Err(CompositionError::StateInUse { state: state.clone() });
+        }
+        // the submachine may return into the call itself to run again
+        if !self.states().contains(return_state) && !self.is_halting_state(return_state) && return_state != state {
+            return Err(CompositionError::UnknownState { state: return_state.clone() });
+        }
+        let mut taken: HashSet<RuleState> = all_states(self).into_iter().collect();
+        let initial = submachine.initial_state();
+        taken.insert(state.clone());
+        let mut names = HashMap::from([(initial.clone(), state.clone())]);
+        for sub_state in all_states(submachine).into_iter().filter(|s| *s != initial) {
+            let name = unique(format!("{}_{}", state, sub_state), &mut taken);
+            names.insert(sub_state, name);
+        }
+        let mut table = Table::new(self.tapes());
+        table.add_caller(self, state, &mut taken);
+        table.add(submachine, &names, Some(return_state), &mut taken);
+        Ok(table.build(self.blank()))
+    }
+}
+
+impl Table {
+    fn new(tapes: usize) -> Table {
+        Table {
+            rules: HashMap::new(),
+            symbols: Vec::new(),
+            states: Vec::new(),
+            accept_states: Vec::new(),
+            reject_states: Vec::new(),
+            tapes,
+        }
+    }
+
+    /// copies the rules of the part with the states renamed by `names`, the states missing there keep their names.
+    /// With an `exit`, the halts of the part continue in it and the accept states of the part are dropped.
+    fn add(&mut self, part: &Ruleset, names: &HashMap<RuleState, RuleState>, exit: Option<&RuleState>, taken: &mut HashSet<RuleState>) {
+        let rename = |state: &RuleState| names.get(state).cloned().unwrap_or_else(|| state.clone());
+        self.add_rules(part, rename, |rule| {
+            if part.reject_states().contains(&rule.next_state) {
+                return None;
+            }
+            exit.filter(|_| rule.is_terminal() || part.accept_states().contains(&rule.next_state))
+        }, taken);
+        if exit.is_none() {
+            self.accept_states.extend(part.accept_states().iter().map(rename));
+        }
+        self.reject_states.extend(part.reject_states().iter().map(rename));
+    }
+
+    /// copies the rules of the caller, the rules entering the called state go on into the submachine
+    fn add_caller(&mut self, caller: &Ruleset, state: &RuleState, taken: &mut HashSet<RuleState>) {
+        self.add_rules(caller, RuleState::clone, |rule| (rule.next_state == *state).then_some(state), taken);
+        self.accept_states.extend(caller.accept_states().iter().cloned());
+        self.reject_states.extend(caller.reject_states().iter().cloned());
+    }
+
+    /// copies every cell of the part. `route` gives the state to continue in instead of the renamed next state of a rule,
+    /// the rules which stop every head go there through a state that moves the heads right and back.
+    fn add_rules<'a>(
+        &mut self,
+        part: &Ruleset,
+        rename: impl Fn(&RuleState) -> RuleState,
+        route: impl Fn(&Rule) -> Option<&'a RuleState>,
+        taken: &mut HashSet<RuleState>,
+    ) {
+        let mut bridges: HashMap<RuleState, RuleState> = HashMap::new();
+        for row in part.symbols() {
+            if !self.symbols.contains(row) {
+                self.symbols.push(row.clone());
+            }
+        }
+        for state in part.states() {
+            let mut cells = HashMap::new();
+            for row in part.symbols() {
+                let rules = part.cell(state, row);
+                if rules.is_empty() {
+                    continue;
+                }
+                let rules = rules.iter().map(|rule| match route(rule) {
+                    Some(target) if rule.is_terminal() => {
+                        let bridge = bridges.entry(target.clone())
+                            .or_insert_with(|| unique(format!("{}_enter", target), taken))
+                            .clone();
+                        Rule::with_tapes(rule.writes.iter().map(|write| (*write, Move::Right)).collect(), bridge)
+                    }
+                    Some(target) => Rule { next_state: target.clone(), ..rule.clone() },
+                    None => Rule { next_state: rename(&rule.next_state), ..rule.clone() },
+                }).collect();
+                cells.insert(row.clone(), rules);
+            }
+            self.states.push(rename(state));
+            self.rules.insert(rename(state), cells);
+        }
+        let mut bridges: Vec<(RuleState, RuleState)> = bridges.into_iter().collect();
+        bridges.sort();
+        for (target, bridge) in bridges {
+            self.add_bridge(bridge, target);
+        }
+    }
+
+    /// the state which keeps the symbols, moves every head left and enters `target`
+    fn add_bridge(&mut self, bridge: RuleState, target: RuleState) {
+        let row = vec![WILDCARD; self.tapes];
+        if !self.symbols.contains(&row) {
+            self.symbols.push(row.clone());
+        }
+        let rule = Rule::with_tapes(vec![(WILDCARD, Move::Left); self.tapes], target);
+        self.states.push(bridge.clone());
+        self.rules.insert(bridge, HashMap::from([(row, vec![rule])]));
+    }
+
+    fn build(self, blank: char) -> Ruleset {
+        let mut ruleset = Ruleset::new(self.rules, self.symbols, self.states);
+        ruleset.set_accept_states(self.accept_states);
+        ruleset.set_reject_states(self.reject_states);
+        ruleset.set_blank(blank);
+        ruleset
+    }
+}
+
+fn check_compatible(first: &Ruleset, second: &Ruleset) -> Result<(), CompositionError> {
+    if first.tapes() != second.tapes() {
+        return Err(CompositionError::TapesMismatch { expected: first.tapes(), found: second.tapes() });
+    }
+    if first.blank() != second.blank() {
+        return Err(CompositionError::BlankMismatch { expected: first.blank(), found: second.blank() });
+    }
+    Ok(())
+}
+
+/// the machine starts in the state of its first column, so it needs one
+fn check_runnable(ruleset: &Ruleset) -> Result<(), CompositionError> {
+    if ruleset.states().is_empty() {
+        return Err(CompositionError::InvalidRuleset { error: RulesetError::NoStates });
+    }
+    Ok(())
+}
+
+/// every state the ruleset mentions: the columns, the next states of the rules, the accept and the reject states
+fn all_states(ruleset: &Ruleset) -> Vec<RuleState> {
+    let next_states = ruleset.states().iter()
+        .flat_map(|state| ruleset.symbols().iter().flat_map(move |row| ruleset.cell(state, row)))
+        .map(|rule| rule.next_state.clone());
+    let mut states = Vec::new();
+    for state in ruleset.states().iter().cloned()
+        .chain(next_states)
+        .chain(ruleset.accept_states().iter().cloned())
+        .chain(ruleset.reject_states().iter().cloned()) {
+        if !states.contains(&state) {
+            states.push(state);
+        }
+    }
+    states
+}
+
+/// the state named `name`, or `{name}_1`, `{name}_2`, ... when the name is taken
+pub(crate) fn unique(name: String, taken: &mut HashSet<RuleState>) -> RuleState {
+    let parse = |name: &str| name.parse::<RuleState>().expect("composed state names are made of valid names");
+    let mut state = parse(&name);
+    let mut suffix = 1;
+    while taken.contains(&state) {
+        state = parse(&format!("{}_{}", name, suffix));
+        suffix += 1;
+    }
+    taken.insert(state.clone());
+    state
+}
+
+impl Display for CompositionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CompositionError::TapesMismatch { expected, found } => write!(f, "Machines have different numbers of tapes: {} and {}", expected, found),
+            CompositionError::BlankMismatch { expected, found } => write!(f, "Machines have different blank symbols: {} and {}", expected, found),
+            CompositionError::StateInUse { state } => write!(f, "State \"{}\" is already used by the machine", state),
+            CompositionError::UnknownState { state } => write!(f, "Return state \"{}\" is not a state of the machine", state),
+            CompositionError::InvalidRuleset { error } => write!(f, "Invalid machine to compose: {}", error),
+        }
+    }
+}
+
+impl Error for CompositionError {}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+    use crate::composition::CompositionError;
+    use crate::ruleset::{Ruleset, RulesetError};
+    use crate::tape::Tape;
+    use crate::turing::Turing;
+
+    // goes right to the end of the word and halts on the blank
+    const TO_END: &str = "\
+|   | 0   |
+|:-:|:-:  |
+| a | a>0 |
+| _ | _!0 |";
+
+    // steps left and replaces "a" with "b" going left
+    const REPLACE_BACK: &str = "\
+|   | 0   | 1   |
+|:-:|:-:  |:-:  |
+| a | a<1 | b<1 |
+| _ | _<1 | _!1 |";
+
+    fn run(rules: Ruleset, tape: &str) -> Turing {
+        let mut turing = Turing::new(Tape::new(tape.chars().collect(), 0, 0), rules.initial_state(), rules);
+        turing.run(100).unwrap();
+        assert!(turing.is_halted());
+        turing
+    }
+
+    #[test]
+    fn test_then() {
+        let rules = Ruleset::from_str(TO_END).unwrap().then(&Ruleset::from_str(REPLACE_BACK).unwrap()).unwrap();
+        let states: Vec<String> = rules.states().iter().map(|s| s.to_string()).collect();
+        assert_eq!(states, vec!["0", "1_enter", "1", "2"]);
+        assert_eq!(rules.find_symbols(&0.into(), &['_']).unwrap().to_string(), "_>1_enter");
+        let turing = run(rules, "aaa");
+        assert_eq!(turing.tape().data().iter().collect::<String>(), "_bbb__");
+        // 3 steps to the end, the handover, 4 steps back and the halt
+        assert_eq!(turing.steps(), 10);
+    }
+
+    #[test]
+    fn test_then_accept_states() {
+        let first = Ruleset::from_str(&format!("accept: done\nreject: 9\n{}", TO_END.replace("_!0", "_<done"))).unwrap();
+        let rules = first.then(&Ruleset::from_str(REPLACE_BACK).unwrap()).unwrap();
+        // entering the accept state moves on to the next machine without the extra state
+        assert_eq!(rules.find_symbols(&0.into(), &['_']).unwrap().to_string(), "_<10");
+        assert!(rules.accept_states().is_empty());
+        assert_eq!(rules.reject_states(), &vec![9.into()]);
+        assert_eq!(run(rules, "aa").tape().data().iter().collect::<String>(), "_ba_");
+    }
+
+    #[test]
+    fn test_call() {
+        // replaces the blanks between the words with "b" up to the "c", skipping the words with the submachine
+        let caller = Ruleset::from_str("\
+|   | 0      | 1      |
+|:-:|:-:     |:-:     |
+| a | a!skip | a!1    |
+| _ | _!skip | b>skip |
+| c | c!1    | c!1    |").unwrap();
+        let skip = Ruleset::from_str("|   | 0   |\n| a | a>0 |\n| * | *!0 |").unwrap();
+        let rules = caller.call(&"skip".parse().unwrap(), &skip, &1.into()).unwrap();
+        let states: Vec<String> = rules.states().iter().map(|s| s.to_string()).collect();
+        assert_eq!(states, vec!["0", "1", "skip_enter", "skip", "1_enter"]);
+        // the submachine halts by the wildcard row, which keeps the symbol
+        assert_eq!(rules.find_symbols(&"skip".parse().unwrap(), &['_']).unwrap().to_string(), "*>1_enter");
+        assert_eq!(run(rules, "aa_a_ac").tape().data().iter().collect::<String>(), "aababac_");
+    }
+
+    #[test]
+    fn test_composition_errors() {
+        let single = Ruleset::from_str(TO_END).unwrap();
+        let double = Ruleset::from_str("|    | 0     |\n| a_ | a>_>0 |").unwrap();
+        assert_eq!(single.then(&double).err(), Some(CompositionError::TapesMismatch { expected: 1, found: 2 }));
+        let mut other_blank = Ruleset::from_str(TO_END).unwrap();
+        other_blank.set_blank('λ');
+        assert_eq!(single.then(&other_blank).err(), Some(CompositionError::BlankMismatch { expected: '_', found: 'λ' }));
+        assert_eq!(single.call(&0.into(), &single, &0.into()).err(), Some(CompositionError::StateInUse { state: 0.into() }));
+        let no_states = Ruleset::from_str("|   |\n|:-:|\n| a |").unwrap();
+        assert!(no_states.states().is_empty());
+        let no_states_error = Some(CompositionError::InvalidRuleset { error: RulesetError::NoStates });
+        assert_eq!(single.then(&no_states).err(), no_states_error);
+        assert_eq!(single.call(&"sub".parse().unwrap(), &no_states, &0.into()).err(), no_states_error);
+        assert_eq!(single.call(&"sub".parse().unwrap(), &single, &5.into()).err(), Some(CompositionError::UnknownState { state: 5.into() }));
+        // returning into the call runs the submachine again
+        assert!(single.call(&"sub".parse().unwrap(), &single, &"sub".parse().unwrap()).is_ok());
+    }
+}
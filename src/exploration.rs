@@ -0,0 +1,152 @@
+use std::collections::VecDeque;
+use crate::rule::{Rule, RuleState};
+use crate::tape::Tape;
+use crate::transition::Transition;
+use crate::turing::Turing;
+
+/// Result of the breadth-first exploration of the configuration tree of a nondeterministic machine.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Exploration {
+    /// some branch halted, or entered an accept state when the ruleset has them.
+    /// Contains the transitions from the initial configuration to the halt.
+    Accepted(Vec<Transition>),
+    /// every branch got stuck without a rule or was rejected before reaching the limits.
+    Rejected,
+    /// no branch halted, but some branches were cut by the depth or the configurations limit.
+    LimitReached,
+}
+
+struct Configuration {
+    state: RuleState,
+    tapes: Vec<Tape>,
+    depth: usize,
+    // index of the parent configuration and the rule which leads from it to this one
+    parent: Option<(usize, Rule)>,
+}
+
+impl Turing {
+    /// explores every branch of the machine breadth-first from the current configuration.
+    /// Branches deeper than `max_depth` steps are cut, and the exploration stops after `max_configurations` configurations.
+    pub fn explore(&self, max_depth: usize, max_configurations: usize) -> Exploration {
+        let ruleset = self.ruleset();
+        if ruleset.accept_states().contains(&self.state()) {
+            return Exploration::Accepted(vec![]);
+        }
+        let mut configurations = vec![Configuration { state: self.state(), tapes: self.tapes().clone(), depth: 0, parent: None }];
+        let mut queue = VecDeque::from([0]);
+        let mut limit_reached = false;
+        while let Some(index) = queue.pop_front() {
+            let configuration = &configurations[index];
+            if configuration.depth >= max_depth {
+                limit_reached = true;
+                continue;
+            }
+            let symbols: Vec<char> = configuration.tapes.iter().map(Tape::read).collect();
+            for rule in ruleset.find_all(&configuration.state, &symbols) {
+                let accepted = ruleset.accept_states().contains(&rule.next_state);
+                if accepted || (rule.is_terminal() && !ruleset.is_recognizer() && !ruleset.is_halting_state(&rule.next_state)) {
+                    let mut path = path_to(&configurations, index);
+                    path.push(Transition::new(configurations[index].state.clone(), &configurations[index].tapes, rule.clone()));
+                    return Exploration::Accepted(path);
+                }
+                // the branch is rejected
+                if rule.is_terminal() || ruleset.is_halting_state(&rule.next_state) {
+                    continue;
+                }
+                if configurations.len() >= max_configurations {
+                    return Exploration::LimitReached;
+                }
+                let configuration = &configurations[index];
+                let mut tapes = configuration.tapes.clone();
+                Transition::new(configuration.state.clone(), &tapes, rule.clone()).apply(&mut tapes);
+                configurations.push(Configuration {
+                    state: rule.next_state.clone(),
+                    tapes,
+                    depth: configuration.depth + 1,
+                    parent: Some((index, rule.clone())),
+                });
+                queue.push_back(configurations.len() - 1);
+            }
+        }
+        if limit_reached {
+            Exploration::LimitReached
+        } else {
+            Exploration::Rejected
+        }
+    }
+}
+
+/// transitions from the root of the tree to the configuration
+fn path_to(configurations: &[Configuration], mut index: usize) -> Vec<Transition> {
+    let mut path = Vec::new();
+    while let Some((parent, rule)) = &configurations[index].parent {
+        let parent_configuration = &configurations[*parent];
+        path.push(Transition::new(parent_configuration.state.clone(), &parent_configuration.tapes, rule.clone()));
+        index = *parent;
+    }
+    path.reverse();
+    path
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+    use crate::exploration::Exploration;
+    use crate::ruleset::Ruleset;
+    use crate::tape::Tape;
+    use crate::turing::Turing;
+
+    // guesses a "b" to stop at, the machine gets stuck on the blank after the input
+    const GUESS_B: &str = "\
+|   | 0       |
+|:-:|:-:      |
+| a | a>0     |
+| b | b>0 b!1 |";
+
+    #[test]
+    fn test_explore_accepted() {
+        let rules = Ruleset::from_str(GUESS_B).unwrap();
+        let turing = Turing::new(Tape::new("abab".chars().collect(), 0, 0), 0, rules);
+        match turing.explore(10, 100) {
+            Exploration::Accepted(path) => {
+                // the shortest branch stops on the first "b"
+                assert_eq!(path.len(), 2);
+                assert_eq!(path.iter().map(|t| t.deltas()[0].head()).collect::<Vec<_>>(), vec![0, 1]);
+                assert_eq!(path.iter().map(|t| t.rule().to_string()).collect::<Vec<_>>(), vec!["a>0", "b!1"]);
+            }
+            _ => panic!("expected an accepting branch"),
+        }
+    }
+
+    #[test]
+    fn test_explore_rejected_and_limits() {
+        let rules = Ruleset::from_str(GUESS_B).unwrap();
+        let turing = Turing::new(Tape::new("aaa".chars().collect(), 0, 0), 0, rules.clone());
+        assert!(matches!(turing.explore(10, 100), Exploration::Rejected));
+
+        let turing = Turing::new(Tape::new("aaab".chars().collect(), 0, 0), 0, rules);
+        assert!(matches!(turing.explore(10, 100), Exploration::Accepted(_)));
+        assert!(matches!(turing.explore(2, 100), Exploration::LimitReached));
+        assert!(matches!(turing.explore(10, 2), Exploration::LimitReached));
+    }
+
+    #[test]
+    fn test_explore_recognizer() {
+        // guesses whether the word ends with "b", halting on "b" rejects the branch
+        let rules = Ruleset::from_str("\
+accept: 1
+|   | 0       |
+|:-:|:-:      |
+| a | a>0     |
+| b | b>0 b!0 |
+| _ | _>1     |").unwrap();
+        let turing = Turing::new(Tape::new("ab".chars().collect(), 0, 0), 0, rules.clone());
+        match turing.explore(10, 100) {
+            Exploration::Accepted(path) => assert_eq!(path.last().unwrap().rule().to_string(), "_>1"),
+            _ => panic!("expected an accepting branch"),
+        }
+        let turing = Turing::new(Tape::new("ab".chars().collect(), 0, 0), 1, rules);
+        assert!(matches!(turing.explore(10, 100), Exploration::Accepted(path) if path.is_empty()));
+    }
+}
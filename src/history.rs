@@ -0,0 +1,99 @@
+use crate::tape::Tape;
+use crate::transition::Transition;
+
+const CHECKPOINT_INTERVAL: usize = 100;
+
+/// Transitions of a run. The tapes are copied only every `interval` steps,
+/// the tapes before any other step are rebuilt from the nearest checkpoint.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct History {
+    transitions: Vec<Transition>,
+    // tapes before the steps 0, interval, 2 * interval, ...
+    checkpoints: Vec<Vec<Tape>>,
+    interval: usize,
+}
+
+impl History {
+    /// starts the history of a machine with the given tapes
+    pub fn new(tapes: Vec<Tape>) -> History {
+        History::with_interval(tapes, CHECKPOINT_INTERVAL)
+    }
+
+    /// starts the history with the tapes copied every `interval` steps, an interval of 0 is taken as 1
+    pub fn with_interval(tapes: Vec<Tape>, interval: usize) -> History {
+        History { transitions: vec![], checkpoints: vec![tapes], interval: interval.max(1) }
+    }
+
+    /// adds the transition, `tapes` are the tapes after it
+    pub fn push(&mut self, transition: Transition, tapes: &[Tape]) {
+        self.transitions.push(transition);
+        if self.transitions.len().is_multiple_of(self.interval) {
+            self.checkpoints.push(tapes.to_vec());
+        }
+    }
+
+    pub fn transitions(&self) -> &Vec<Transition> {
+        &self.transitions
+    }
+
+    pub fn len(&self) -> usize {
+        self.transitions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.transitions.is_empty()
+    }
+
+    /// tapes before the step `step`, counted from 0. The step `len()` gives the tapes after the last transition,
+    /// a later step is not in the history.
+    pub fn tapes(&self, step: usize) -> Option<Vec<Tape>> {
+        if step > self.transitions.len() {
+            return None;
+        }
+        let checkpoint = step / self.interval;
+        let mut tapes = self.checkpoints[checkpoint].clone();
+        for transition in &self.transitions[checkpoint * self.interval..step] {
+            transition.apply(&mut tapes);
+        }
+        Some(tapes)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+    use crate::history::History;
+    use crate::ruleset::Ruleset;
+    use crate::tape::Tape;
+    use crate::turing::Turing;
+
+    #[test]
+    fn test_history_tapes() {
+        // replaces "a" with "b" going right
+        let rules = Ruleset::from_str("|   | 0   |\n| a | b>0 |\n| _ | _!0 |").unwrap();
+        let mut turing = Turing::new(Tape::new("aaaaa".chars().collect(), 0, 0), 0, rules);
+        let mut history = History::with_interval(turing.tapes().clone(), 2);
+        let mut expected = vec![turing.tape().to_string()];
+        while !turing.is_halted() {
+            let transition = turing.step().unwrap();
+            history.push(transition, turing.tapes());
+            expected.push(turing.tape().to_string());
+        }
+        assert_eq!(history.len(), 6);
+        assert_eq!(history.checkpoints.len(), 4);
+        for (step, tape) in expected.iter().enumerate() {
+            assert_eq!(&history.tapes(step).unwrap()[0].to_string(), tape);
+            assert_eq!(history.tapes(step).unwrap()[0].head(), step.min(5) as isize);
+        }
+        assert!(history.tapes(7).is_none());
+    }
+
+    #[test]
+    fn test_history_interval() {
+        let mut history = History::with_interval(vec![Tape::new("a".chars().collect(), 0, 0)], 0);
+        assert_eq!(history.interval, 1);
+        let mut turing = Turing::new(Tape::new("a".chars().collect(), 0, 0), 0, Ruleset::from_str("|   | 0   |\n| a | b>0 |").unwrap());
+        history.push(turing.step().unwrap(), turing.tapes());
+        assert_eq!(history.tapes(1).unwrap()[0].to_string(), " b [_]");
+    }
+}
@@ -0,0 +1,121 @@
+use std::collections::{HashMap, HashSet};
+use crate::composition::unique;
+use crate::rule::{Move, Rule, RuleState, WILDCARD};
+use crate::tape::SPACE;
+
+/// the state read from a name of another simulator: the characters other than letters, digits and `_` are replaced
+/// with `_`, so `go-back` becomes `go_back`. The readers reject a name which clashes with another one once replaced.
+pub(crate) fn state_name(name: &str) -> Option<RuleState> {
+    name.chars().map(|c| if c.is_alphanumeric() { c } else { '_' }).collect::<String>().parse().ok()
+}
+
+/// The states which let a rule stay on every tape and still go on. In the tables only a terminal rule may stop
+/// every head, so such a rule moves the heads right to a `{state}_enter` state, which moves them back left
+/// and enters the state. Every state gets a single bridge, however many rules go through it.
+#[derive(Default)]
+pub(crate) struct Bridges {
+    bridges: Vec<(RuleState, RuleState)>,
+}
+
+impl Bridges {
+    /// the rule which writes the symbols and enters `target` through its bridge
+    pub fn rule(&mut self, writes: Vec<char>, target: &RuleState, taken: &mut HashSet<RuleState>) -> Rule {
+        let bridge = match self.bridges.iter().find(|(t, _)| t == target) {
+            Some((_, bridge)) => bridge.clone(),
+            None => {
+                let bridge = unique(format!("{}_enter", target), taken);
+                self.bridges.push((target.clone(), bridge.clone()));
+                bridge
+            }
+        };
+        Rule::with_tapes(writes.into_iter().map(|write| (write, Move::Right)).collect(), bridge)
+    }
+
+    /// adds the bridges after the states, with the wildcard row they read
+    pub fn add(self, rules: &mut HashMap<RuleState, HashMap<Vec<char>, Vec<Rule>>>, rows: &mut Vec<Vec<char>>, states: &mut Vec<RuleState>, tapes: usize) {
+        let row = vec![WILDCARD; tapes];
+        if !self.bridges.is_empty() && !rows.contains(&row) {
+            rows.push(row.clone());
+        }
+        for (target, bridge) in self.bridges {
+            let rule = Rule::with_tapes(vec![(WILDCARD, Move::Left); tapes], target);
+            rules.insert(bridge.clone(), HashMap::from([(row.clone(), vec![rule])]));
+            states.push(bridge);
+        }
+    }
+}
+
+/// Reads the symbols of a format whose blank is not a symbol, like the space of YAML or the empty element of JFLAP.
+/// The blank is read as `_`, the blank of the tables, so a machine which also uses `_` can't be read.
+/// The formats have no wildcard `*`.
+#[derive(Default)]
+pub(crate) struct Symbols {
+    blank: bool,
+    underscore: bool,
+}
+
+impl Symbols {
+    /// the blank of the format
+    pub fn blank(&mut self) -> char {
+        self.blank = true;
+        SPACE
+    }
+
+    /// the symbol, or `None` for `*`
+    pub fn symbol(&mut self, c: char) -> Option<char> {
+        match c {
+            WILDCARD => None,
+            SPACE => {
+                self.underscore = true;
+                Some(SPACE)
+            }
+            c => Some(c),
+        }
+    }
+
+    /// whether both the blank and `_` were read
+    pub fn clash(&self) -> bool {
+        self.blank && self.underscore
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::{HashMap, HashSet};
+    use crate::import::{state_name, Bridges, Symbols};
+    use crate::rule::{Move, Rule, RuleState};
+
+    #[test]
+    fn test_state_name() {
+        assert_eq!(state_name("go-back"), Some("go_back".parse().unwrap()));
+        assert_eq!(state_name("q 1"), Some("q_1".parse().unwrap()));
+        assert_eq!(state_name("007"), Some(7.into()));
+        assert_eq!(state_name(""), None);
+    }
+
+    #[test]
+    fn test_bridges() {
+        let target: RuleState = "a".parse().unwrap();
+        let mut taken: HashSet<RuleState> = HashSet::from([target.clone(), "a_enter".parse().unwrap()]);
+        let mut bridges = Bridges::default();
+        let rule = bridges.rule(vec!['x', 'y'], &target, &mut taken);
+        assert_eq!(rule, Rule::with_tapes(vec![('x', Move::Right), ('y', Move::Right)], "a_enter_1".parse::<RuleState>().unwrap()));
+        assert_eq!(bridges.rule(vec!['z', 'z'], &target, &mut taken).next_state, rule.next_state);
+        let (mut rules, mut rows, mut states) = (HashMap::new(), vec![vec!['x', 'y']], vec![target.clone()]);
+        bridges.add(&mut rules, &mut rows, &mut states, 2);
+        assert_eq!(rows, vec![vec!['x', 'y'], vec!['*', '*']]);
+        assert_eq!(states, vec![target.clone(), rule.next_state.clone()]);
+        assert_eq!(rules[&rule.next_state][&vec!['*', '*']], vec![Rule::with_tapes(vec![('*', Move::Left); 2], target)]);
+    }
+
+    #[test]
+    fn test_symbols() {
+        let mut symbols = Symbols::default();
+        assert_eq!(symbols.symbol('a'), Some('a'));
+        assert_eq!(symbols.symbol('*'), None);
+        assert_eq!(symbols.blank(), '_');
+        assert!(!symbols.clash());
+        assert_eq!(symbols.symbol('_'), Some('_'));
+        assert!(symbols.clash());
+    }
+}
@@ -0,0 +1,534 @@
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+use std::fmt::Display;
+use std::str::FromStr;
+use roxmltree::{Document, Node};
+use crate::composition::unique;
+use crate::import::{state_name, Bridges, Symbols};
+use crate::rule::{Move, Rule, RuleState, WILDCARD};
+use crate::ruleset::Ruleset;
+use crate::tape::{Tape, SPACE};
+use crate::turing::Turing;
+
+/// `~` reads any symbol and writes back the read one in JFLAP, like the wildcard `*` of the tables
+const JFLAP_WILDCARD: char = '~';
+
+/// A Turing machine drawn in JFLAP and saved as a `.jff` file: the states with their ids and names, the initial and
+/// the final states, and the transitions reading, writing and moving every tape. An empty symbol is the blank:
+/// ```text
+/// <structure>
+///   <type>turing</type>
+///   <automaton>
+///     <state id="0" name="q0"><x>100.0</x><y>100.0</y><initial/></state>
+///     <state id="1" name="q1"><x>250.0</x><y>100.0</y><final/></state>
+///     <transition><from>0</from><to>0</to><read>a</read><write>b</write><move>R</move></transition>
+///     <transition><from>0</from><to>1</to><read/><write/><move>S</move></transition>
+///   </automaton>
+/// </structure>
+/// ```
+/// JFLAP halts and accepts in the final states, they are the accept states of the ruleset.
+/// Multi-tape machines declare `<tapes>` and give the tape of every symbol and move, like `<read tape="2">a</read>`.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct JflapMachine {
+    ruleset: Ruleset,
+    state: RuleState,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum JflapParseError {
+    InvalidXml { message: String },
+    /// the file holds another kind of automaton
+    NotTuring { kind: String },
+    MissingElement { element: String },
+    /// the name of the state clashes with another one
+    InvalidState { state: String },
+    /// no state has the id
+    UnknownState { id: String },
+    /// the symbol is not a single character, is the wildcard `*`, or is `_` in a machine which uses the blank
+    InvalidSymbol { symbol: String },
+    /// the transition misses a symbol or a move of a tape, or has an unknown move
+    InvalidTransition { from: String, to: String },
+}
+
+impl JflapMachine {
+    /// the machine starting in `state`
+    pub fn new(ruleset: Ruleset, state: impl Into<RuleState>) -> JflapMachine {
+        JflapMachine { ruleset, state: state.into() }
+    }
+
+    pub fn ruleset(&self) -> &Ruleset {
+        &self.ruleset
+    }
+
+    pub fn state(&self) -> RuleState {
+        self.state.clone()
+    }
+
+    /// JFLAP files keep no input, the machine starts on the given tapes
+    pub fn into_turing(self, tapes: Vec<Tape>) -> Turing {
+        Turing::with_tapes(tapes, self.state, self.ruleset)
+    }
+}
+
+impl Display for JflapMachine {
+    /// writes the states in the order of the table on a grid. The accept states are final,
+    /// and the rules of the accept and the reject states are left out, because the machine halts in them.
+    /// A rule which stops every head and halts the machine goes to a state without transitions:
+    /// its next state, or `{state}_halted` when the next state has transitions.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let ruleset = &self.ruleset;
+        let mut transitions: Vec<(&RuleState, Vec<char>, Rule)> = Vec::new();
+        for state in ruleset.states().iter().filter(|state| !ruleset.is_halting_state(state)) {
+            for row in ruleset.symbols() {
+                let rules = ruleset.cell(state, row);
+                if rules.is_empty() {
+                    continue;
+                }
+                for reads in reads(ruleset, state, row) {
+                    for rule in rules {
+                        let keeps = reads.iter().zip(rule.writes()).all(|(read, write)| *write == WILDCARD || write == read);
+                        // JFLAP halts without a transition
+                        if !(rule.is_terminal() && keeps && rule.next_state == *state) {
+                            transitions.push((state, reads.clone(), rule.clone()));
+                        }
+                    }
+                }
+            }
+        }
+        let mut states: Vec<RuleState> = ruleset.states().clone();
+        let entered = transitions.iter().map(|(_, _, rule)| &rule.next_state);
+        for state in entered.chain(ruleset.accept_states()).chain(ruleset.reject_states()).chain([&self.state]) {
+            if !states.contains(state) {
+                states.push(state.clone());
+            }
+        }
+        let mut taken: HashSet<RuleState> = states.iter().cloned().collect();
+        let mut halted: HashMap<RuleState, RuleState> = HashMap::new();
+        let has_transitions = |state: &RuleState| transitions.iter().any(|(from, _, _)| *from == state);
+        let halting: Vec<bool> = transitions.iter().map(|(_, _, rule)| {
+            rule.is_terminal() && !ruleset.accept_states().contains(&rule.next_state) && has_transitions(&rule.next_state)
+        }).collect();
+        for ((_, _, rule), halting) in transitions.iter_mut().zip(halting) {
+            if halting {
+                rule.next_state = halted.entry(rule.next_state.clone())
+                    .or_insert_with(|| unique(format!("{}_halted", rule.next_state), &mut taken))
+                    .clone();
+            }
+        }
+        let mut halted: Vec<RuleState> = halted.into_values().collect();
+        halted.sort();
+        states.extend(halted);
+        let id = |state: &RuleState| states.iter().position(|s| s == state).expect("every state is listed");
+
+        writeln!(f, r#"<?xml version="1.0" encoding="UTF-8" standalone="no"?>"#)?;
+        writeln!(f, "<structure>")?;
+        writeln!(f, "\t<type>turing</type>")?;
+        if ruleset.tapes() > 1 {
+            writeln!(f, "\t<tapes>{}</tapes>", ruleset.tapes())?;
+        }
+        writeln!(f, "\t<automaton>")?;
+        for (i, state) in states.iter().enumerate() {
+            writeln!(f, "\t\t<state id=\"{}\" name=\"{}\">", i, escape(state.name()))?;
+            writeln!(f, "\t\t\t<x>{:.1}</x>", 100.0 + 150.0 * (i % 5) as f64)?;
+            writeln!(f, "\t\t\t<y>{:.1}</y>", 100.0 + 150.0 * (i / 5) as f64)?;
+            if *state == self.state {
+                writeln!(f, "\t\t\t<initial/>")?;
+            }
+            if ruleset.accept_states().contains(state) {
+                writeln!(f, "\t\t\t<final/>")?;
+            }
+            writeln!(f, "\t\t</state>")?;
+        }
+        for (state, row, rule) in &transitions {
+            writeln!(f, "\t\t<transition>")?;
+            writeln!(f, "\t\t\t<from>{}</from>", id(state))?;
+            writeln!(f, "\t\t\t<to>{}</to>", id(&rule.next_state))?;
+            for (tape, ((read, write), mov)) in row.iter().zip(rule.writes()).zip(rule.moves()).enumerate() {
+                let attribute = if ruleset.tapes() > 1 { format!(" tape=\"{}\"", tape + 1) } else { String::new() };
+                element(f, "read", &attribute, *read, ruleset.blank())?;
+                element(f, "write", &attribute, *write, ruleset.blank())?;
+                let mov = match mov {
+                    Move::Left => "L",
+                    Move::Right => "R",
+                    Move::Stop => "S",
+                };
+                writeln!(f, "\t\t\t<move{}>{}</move>", attribute, mov)?;
+            }
+            writeln!(f, "\t\t</transition>")?;
+        }
+        writeln!(f, "\t</automaton>")?;
+        writeln!(f, "</structure>")
+    }
+}
+
+/// the symbols the transitions of the row read. A row with wildcards is written with `~`, unless a row
+/// with fewer wildcards takes some of its symbols: JFLAP would take both, so the row is split into the symbols it is left with.
+fn reads(ruleset: &Ruleset, state: &RuleState, row: &[char]) -> Vec<Vec<char>> {
+    let overlaps = |other: &Vec<char>| other.iter().zip(row).all(|(o, r)| o == r || *o == WILDCARD || *r == WILDCARD);
+    let overlapped = row.contains(&WILDCARD)
+        && ruleset.symbols().iter().any(|other| other != row && overlaps(other) && !ruleset.cell(state, other).is_empty());
+    if !overlapped {
+        return vec![row.to_vec()];
+    }
+    let mut symbols: Vec<char> = ruleset.alphabet().clone();
+    let writes = ruleset.states().iter().flat_map(|state| ruleset.symbols().iter().flat_map(move |row| ruleset.cell(state, row)));
+    for symbol in [ruleset.blank()].iter().chain(writes.flat_map(|rule| rule.writes())) {
+        if *symbol != WILDCARD && !symbols.contains(symbol) {
+            symbols.push(*symbol);
+        }
+    }
+    let mut reads = vec![vec![]];
+    for symbol in row {
+        let choices = if *symbol == WILDCARD { symbols.clone() } else { vec![*symbol] };
+        reads = reads.into_iter().flat_map(|read: Vec<char>| choices.iter().map(move |c| [read.as_slice(), &[*c]].concat())).collect();
+    }
+    reads.retain(|read| ruleset.matching_row(state, read).is_some_and(|matching| matching == row));
+    reads
+}
+
+/// a read or a written symbol, the blank is an empty element
+fn element(f: &mut fmt::Formatter, name: &str, attribute: &str, symbol: char, blank: char) -> fmt::Result {
+    match symbol {
+        symbol if symbol == blank => writeln!(f, "\t\t\t<{}{}/>", name, attribute),
+        WILDCARD => writeln!(f, "\t\t\t<{}{}>{}</{}>", name, attribute, JFLAP_WILDCARD, name),
+        symbol => writeln!(f, "\t\t\t<{}{}>{}</{}>", name, attribute, escape(&symbol.to_string()), name),
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+impl FromStr for JflapMachine {
+    type Err = JflapParseError;
+
+    /// reads a machine saved by JFLAP. The states are the `<state>` or the `<block>` elements, the initial state becomes
+    /// the first column of the table, and the blank is `_`. A transition which stays on every tape and doesn't go
+    /// to a final state goes through a bridge to its state.
+    /// Every state which is not final gets a `*` row which halts the machine, for the symbols without transitions.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let document = Document::parse(s).map_err(|e| JflapParseError::InvalidXml { message: e.to_string() })?;
+        let structure = document.root_element();
+        let missing = |element: &str| JflapParseError::MissingElement { element: element.to_string() };
+        let kind = text(child(structure, "type").ok_or_else(|| missing("type"))?);
+        if kind != "turing" {
+            return Err(JflapParseError::NotTuring { kind });
+        }
+        let tapes = match child(structure, "tapes") {
+            Some(tapes) => text(tapes).parse().ok().filter(|tapes| *tapes > 0).ok_or_else(|| missing("tapes"))?,
+            None => 1,
+        };
+        let automaton = child(structure, "automaton").unwrap_or(structure);
+
+        let mut ids: HashMap<&str, RuleState> = HashMap::new();
+        let mut states = Vec::new();
+        let mut initial = None;
+        let mut accept_states = Vec::new();
+        for node in automaton.children().filter(|node| node.has_tag_name("state") || node.has_tag_name("block")) {
+            let id = node.attribute("id").ok_or_else(|| missing("id"))?;
+            let name = node.attribute("name").map_or_else(|| format!("q{}", id), str::to_string);
+            let state = match state_name(&name) {
+                Some(state) if !states.contains(&state) => state,
+                _ => return Err(JflapParseError::InvalidState { state: name }),
+            };
+            if child(node, "initial").is_some() {
+                initial = Some(state.clone());
+            }
+            if child(node, "final").is_some() {
+                accept_states.push(state.clone());
+            }
+            ids.insert(id, state.clone());
+            states.push(state);
+        }
+        let initial = initial.ok_or_else(|| missing("initial"))?;
+        let find = |node: Option<Node>, element: &str| -> Result<RuleState, JflapParseError> {
+            let id = text(node.ok_or_else(|| missing(element))?);
+            ids.get(id.as_str()).cloned().ok_or(JflapParseError::UnknownState { id })
+        };
+
+        let mut symbols = Symbols::default();
+        let mut rules: HashMap<RuleState, HashMap<Vec<char>, Vec<Rule>>> = HashMap::new();
+        let mut rows: Vec<Vec<char>> = Vec::new();
+        let mut bridges = Bridges::default();
+        let mut taken: HashSet<RuleState> = states.iter().cloned().collect();
+        for transition in automaton.children().filter(|node| node.has_tag_name("transition")) {
+            let from = find(child(transition, "from"), "from")?;
+            let to = find(child(transition, "to"), "to")?;
+            let invalid = || JflapParseError::InvalidTransition { from: from.to_string(), to: to.to_string() };
+            let mut row = Vec::new();
+            let mut writes = Vec::new();
+            let mut moves = Vec::new();
+            for tape in 1..=tapes {
+                let element = |name: &str| transition.children()
+                    .filter(|node| node.has_tag_name(name))
+                    .find(|node| node.attribute("tape").map_or(tapes == 1, |t| t == tape.to_string()))
+                    .ok_or_else(invalid);
+                row.push(read(&mut symbols, element("read")?)?);
+                writes.push(read(&mut symbols, element("write")?)?);
+                moves.push(match text(element("move")?).as_str() {
+                    "L" => Move::Left,
+                    "R" => Move::Right,
+                    "S" => Move::Stop,
+                    _ => return Err(invalid()),
+                });
+            }
+            let rule = if moves.iter().all(|mov| *mov == Move::Stop) && !accept_states.contains(&to) {
+                bridges.rule(writes, &to, &mut taken)
+            } else {
+                Rule::with_tapes(writes.into_iter().zip(moves).collect(), to)
+            };
+            if !rows.contains(&row) {
+                rows.push(row.clone());
+            }
+            rules.entry(from).or_default().entry(row).or_default().push(rule);
+        }
+        // JFLAP halts when no transition reads the symbols
+        let wildcards = vec![WILDCARD; tapes];
+        for state in states.iter().filter(|state| !accept_states.contains(state)) {
+            let cells = rules.entry(state.clone()).or_default();
+            if !cells.contains_key(&wildcards) {
+                cells.insert(wildcards.clone(), vec![Rule::with_tapes(vec![(WILDCARD, Move::Stop); tapes], state.clone())]);
+            }
+        }
+        if rules.values().any(|cells| cells.contains_key(&wildcards)) && !rows.contains(&wildcards) {
+            rows.push(wildcards.clone());
+        }
+        bridges.add(&mut rules, &mut rows, &mut states, tapes);
+        if symbols.clash() {
+            return Err(JflapParseError::InvalidSymbol { symbol: SPACE.to_string() });
+        }
+        // the initial state goes first
+        states.sort_by_key(|state| *state != initial);
+        let mut ruleset = Ruleset::new(rules, rows, states);
+        ruleset.set_accept_states(accept_states);
+        Ok(JflapMachine { ruleset, state: initial })
+    }
+}
+
+/// an empty element is the blank and `~` the wildcard
+fn read(symbols: &mut Symbols, node: Node) -> Result<char, JflapParseError> {
+    let text = text(node);
+    let mut chars = text.chars();
+    let symbol = match (chars.next(), chars.next()) {
+        (None, _) => Some(symbols.blank()),
+        (Some(JFLAP_WILDCARD), None) => Some(WILDCARD),
+        (Some(c), None) => symbols.symbol(c),
+        _ => None,
+    };
+    symbol.ok_or(JflapParseError::InvalidSymbol { symbol: text })
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|child| child.has_tag_name(name))
+}
+
+fn text(node: Node) -> String {
+    node.text().unwrap_or("").trim().to_string()
+}
+
+impl Display for JflapParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            JflapParseError::InvalidXml { message } => write!(f, "Invalid XML: {}", message),
+            JflapParseError::NotTuring { kind } => write!(f, "Expected a Turing machine, found {}", kind),
+            JflapParseError::MissingElement { element } => write!(f, "Missing element: {}", element),
+            JflapParseError::InvalidState { state } => write!(f, "Invalid state: {}", state),
+            JflapParseError::UnknownState { id } => write!(f, "Unknown state id: {}", id),
+            JflapParseError::InvalidSymbol { symbol } => write!(f, "Invalid symbol: {}", symbol),
+            JflapParseError::InvalidTransition { from, to } => write!(f, "Invalid transition from {} to {}", from, to),
+        }
+    }
+}
+
+impl Error for JflapParseError {}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+    use crate::jflap::{JflapMachine, JflapParseError};
+    use crate::outcome::{Outcome, Verdict};
+    use crate::ruleset::Ruleset;
+    use crate::tape::Tape;
+
+    // replaces "a" with "b", then stays on the blank twice, writing "c" the second time
+    const REPLACE: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="no"?><!--Created with JFLAP 7.1.--><structure>
+	<type>turing</type>
+	<automaton>
+		<!--The list of states.-->
+		<block id="0" name="q0">
+			<tag>Machine0</tag>
+			<x>63.0</x>
+			<y>105.0</y>
+			<initial/>
+		</block>
+		<block id="1" name="q1">
+			<tag>Machine1</tag>
+			<x>213.0</x>
+			<y>105.0</y>
+		</block>
+		<block id="2" name="q2">
+			<tag>Machine2</tag>
+			<x>363.0</x>
+			<y>105.0</y>
+			<final/>
+		</block>
+		<!--The list of transitions.-->
+		<transition>
+			<from>0</from>
+			<to>0</to>
+			<read>a</read>
+			<write>b</write>
+			<move>R</move>
+		</transition>
+		<transition>
+			<from>0</from>
+			<to>1</to>
+			<read/>
+			<write/>
+			<move>S</move>
+		</transition>
+		<transition>
+			<from>1</from>
+			<to>2</to>
+			<read/>
+			<write>c</write>
+			<move>S</move>
+		</transition>
+	</automaton>
+</structure>"#;
+
+    #[test]
+    fn test_jflap_from_str() {
+        let machine = JflapMachine::from_str(REPLACE).unwrap();
+        assert_eq!(machine.ruleset().to_string(), Ruleset::from_str("\
+accept: q2
+|   | q0   | q1   | q2 | q1_enter |
+|:-:|:-:   |:-:   |:-: |:-:       |
+| a | b>q0 |      |    |          |
+| _ | _>q1_enter | c!q2 | |        |
+| * | *!q0 | *!q1 |    | *<q1     |").unwrap().to_string());
+        let mut turing = machine.into_turing(vec![Tape::new("aa".chars().collect(), 0, 0)]);
+        turing.run(100).unwrap();
+        assert_eq!(turing.verdict(), Some(Verdict::Accept));
+        assert_eq!(turing.tape().to_string(), " b  b [c] _ ");
+    }
+
+    #[test]
+    fn test_jflap_display() {
+        // the wildcard row is split, because the row of "a" takes one of its symbols
+        let ruleset = Ruleset::from_str("\
+|   | 0   | 1 |
+|:-:|:-:  |:-:|
+| a | a>0 |   |
+| * | b!1 |   |").unwrap();
+        assert_eq!(JflapMachine::new(ruleset, 0).to_string(), r#"<?xml version="1.0" encoding="UTF-8" standalone="no"?>
+<structure>
+	<type>turing</type>
+	<automaton>
+		<state id="0" name="0">
+			<x>100.0</x>
+			<y>100.0</y>
+			<initial/>
+		</state>
+		<state id="1" name="1">
+			<x>250.0</x>
+			<y>100.0</y>
+		</state>
+		<transition>
+			<from>0</from>
+			<to>0</to>
+			<read>a</read>
+			<write>a</write>
+			<move>R</move>
+		</transition>
+		<transition>
+			<from>0</from>
+			<to>1</to>
+			<read/>
+			<write>b</write>
+			<move>S</move>
+		</transition>
+		<transition>
+			<from>0</from>
+			<to>1</to>
+			<read>b</read>
+			<write>b</write>
+			<move>S</move>
+		</transition>
+	</automaton>
+</structure>
+"#);
+    }
+
+    #[test]
+    fn test_jflap_quirks() {
+        // a state without a name is named after its id, `~` reads any symbol, and a final state is entered without a bridge
+        let machine = JflapMachine::from_str(r#"<structure><type>turing</type><automaton>
+<state id="0"><initial/></state>
+<state id="1" name="a&lt;b"><final/></state>
+<transition><from>0</from><to>0</to><read>a</read><write>&amp;</write><move>R</move></transition>
+<transition><from>0</from><to>1</to><read>~</read><write>~</write><move>S</move></transition>
+</automaton></structure>"#).unwrap();
+        assert_eq!(machine.ruleset().to_string(), Ruleset::from_str("\
+accept: a_b
+|   | q0  | a_b |
+|:-:|:-:  |:-: |
+| a | &>q0 |    |
+| * | *!a_b |   |").unwrap().to_string());
+        let text = machine.to_string();
+        let mut turing = machine.into_turing(vec![Tape::new("aac".chars().collect(), 0, 0)]);
+        turing.run(100).unwrap();
+        assert_eq!(turing.verdict(), Some(Verdict::Accept));
+        assert_eq!(turing.tape().to_string(), " &  & [c]");
+        // the symbols are escaped, and `~` is split into the symbols the machine knows
+        assert!(text.contains("<write>&amp;</write>") && text.contains("<read>&amp;</read>") && text.contains("<read/>\n\t\t\t<write>~</write>"));
+    }
+
+    #[test]
+    fn test_jflap_halted() {
+        // a rule which halts in a state with transitions goes to a state without any
+        let ruleset = Ruleset::from_str("\
+|   | 0   | 1   |
+|:-:|:-:  |:-:  |
+| a | b!1 | a>1 |").unwrap();
+        let text = JflapMachine::new(ruleset, 0).to_string();
+        assert!(text.contains(r#"<state id="2" name="1_halted">"#));
+        let mut turing = JflapMachine::from_str(&text).unwrap().into_turing(vec![Tape::new(vec!['a'], 0, 0)]);
+        assert_eq!(turing.run(100).unwrap().outcome(), &Outcome::Halted);
+        assert_eq!(turing.state(), "1_halted".parse().unwrap());
+        assert_eq!(turing.tape().to_string(), "[b] _ ");
+    }
+
+    #[test]
+    fn test_jflap_tapes() {
+        let two_tapes = Ruleset::from_str("\
+|    | 0     |
+|:-:|:-:    |
+| a* | b>*<0 |").unwrap();
+        let text = JflapMachine::new(two_tapes.clone(), 0).to_string();
+        assert!(text.contains("<tapes>2</tapes>") && text.contains(r#"<read tape="2">~</read>"#) && text.contains(r#"<move tape="2">L</move>"#));
+        let machine = JflapMachine::from_str(&text).unwrap();
+        assert_eq!(machine.ruleset().cell(&0.into(), &['a', '*']), two_tapes.cell(&0.into(), &['a', '*']));
+        // a transition which leaves out a tape
+        let missing = text.replacen(r#"<move tape="2">L</move>"#, "", 1);
+        assert_eq!(JflapMachine::from_str(&missing).err(), Some(JflapParseError::InvalidTransition { from: "0".to_string(), to: "0".to_string() }));
+        assert_eq!(JflapMachine::from_str(&text.replace("<tapes>2</tapes>", "<tapes>0</tapes>")).err(), Some(JflapParseError::MissingElement { element: "tapes".to_string() }));
+    }
+
+    #[test]
+    fn test_jflap_errors() {
+        assert_eq!(JflapMachine::from_str("<structure><type>fa</type></structure>").err(), Some(JflapParseError::NotTuring { kind: "fa".to_string() }));
+        assert_eq!(JflapMachine::from_str(&REPLACE.replace("<initial/>", "")).err(), Some(JflapParseError::MissingElement { element: "initial".to_string() }));
+        assert_eq!(JflapMachine::from_str(&REPLACE.replace("<to>2</to>", "<to>3</to>")).err(), Some(JflapParseError::UnknownState { id: "3".to_string() }));
+        assert_eq!(JflapMachine::from_str(&REPLACE.replace("<move>R</move>", "<move>X</move>")).err(), Some(JflapParseError::InvalidTransition { from: "q0".to_string(), to: "q0".to_string() }));
+        assert!(matches!(JflapMachine::from_str("<structure>").err(), Some(JflapParseError::InvalidXml { .. })));
+        assert_eq!(JflapMachine::from_str(&REPLACE.replace("<read>a</read>", "<read>ab</read>")).err(), Some(JflapParseError::InvalidSymbol { symbol: "ab".to_string() }));
+        assert_eq!(JflapMachine::from_str(&REPLACE.replace("<read>a</read>", "<read>*</read>")).err(), Some(JflapParseError::InvalidSymbol { symbol: "*".to_string() }));
+        // the blank is read as `_`
+        assert_eq!(JflapMachine::from_str(&REPLACE.replace("<write>b</write>", "<write>_</write>")).err(), Some(JflapParseError::InvalidSymbol { symbol: "_".to_string() }));
+        assert_eq!(JflapMachine::from_str(&REPLACE.replace(r#"name="q1""#, r#"name="q-0""#).replace(r#"name="q0""#, r#"name="q_0""#)).err(), Some(JflapParseError::InvalidState { state: "q-0".to_string() }));
+    }
+}
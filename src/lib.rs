@@ -4,9 +4,40 @@ mod turing;
 mod tape;
 mod ruleset;
 mod transition;
+mod history;
+mod exploration;
+mod outcome;
+mod breakpoint;
+mod observer;
+mod snapshot;
+mod statistics;
+mod composition;
+mod compiler;
+mod universal;
+mod import;
+mod yaml;
+mod jflap;
+mod morphett;
+mod lint;
+#[cfg(feature = "serde")]
+mod serialization;
 
 pub use turing::{Turing, TuringError};
 pub use tape::{Tape};
 pub use ruleset::{Ruleset, RulesetError, RulesetParseError};
-pub use rule::{Rule, RuleState, Move};
-pub use transition::Transition;
\ No newline at end of file
+pub use rule::{Rule, RuleState, Move, WILDCARD};
+pub use transition::{Delta, Transition, Transitions};
+pub use history::History;
+pub use exploration::Exploration;
+pub use outcome::{Outcome, Run, Verdict};
+pub use breakpoint::{Breakpoint, BreakpointParseError};
+pub use observer::Observer;
+pub use snapshot::{Snapshot, SnapshotError, SnapshotParseError};
+pub use statistics::Statistics;
+pub use composition::CompositionError;
+pub use compiler::CompileError;
+pub use universal::{Encoding, EncodingError};
+pub use yaml::{YamlMachine, YamlError, YamlParseError};
+pub use jflap::{JflapMachine, JflapParseError};
+pub use morphett::{MorphettMachine, MorphettError, MorphettParseError};
+pub use lint::Lint;
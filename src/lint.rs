@@ -0,0 +1,180 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::fmt::Display;
+use crate::rule::{Rule, RuleState, WILDCARD};
+use crate::ruleset::Ruleset;
+
+/// A problem of a ruleset found without running it, see `Ruleset::check`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Lint {
+    /// no rule leads to the state from the initial state
+    UnreachableState { state: RuleState },
+    /// a rule of the cell goes to a state which is neither a column of the table nor an accept or a reject state
+    UnknownState { state: RuleState, symbols: Vec<char>, next_state: RuleState },
+    /// a rule of the cell writes a symbol which has no row in the table
+    UnknownSymbol { state: RuleState, symbols: Vec<char>, symbol: char },
+    /// the state has no rule for the row, or for the blank of a single-tape machine, which every state may read
+    /// once the head leaves the input. The machine stops there with `RuleNotFound`
+    MissingRule { state: RuleState, symbols: Vec<char> },
+    /// no halting rule or halting state can be reached from the state, once there the machine never halts
+    NoHalt { state: RuleState },
+}
+
+impl Ruleset {
+    /// finds the problems of the table which show up only at runtime otherwise.
+    /// Reachability is counted from the initial state, the first column of the table.
+    pub fn check(&self) -> Vec<Lint> {
+        let mut lints = Vec::new();
+        let reachable = self.reachable_states();
+        for state in self.states().iter().filter(|state| !reachable.contains(state)) {
+            lints.push(Lint::UnreachableState { state: state.clone() });
+        }
+        for (state, row, rule) in self.rules() {
+            if !self.states().contains(&rule.next_state) && !self.is_halting_state(&rule.next_state) {
+                lints.push(Lint::UnknownState { state: state.clone(), symbols: row.clone(), next_state: rule.next_state.clone() });
+            }
+        }
+        for (state, row, rule) in self.rules() {
+            let mut unknown = Vec::new();
+            for symbol in rule.writes.iter().filter(|write| **write != WILDCARD && !self.alphabet().contains(write)) {
+                if !unknown.contains(symbol) {
+                    unknown.push(*symbol);
+                    lints.push(Lint::UnknownSymbol { state: state.clone(), symbols: row.clone(), symbol: *symbol });
+                }
+            }
+        }
+        // with several tapes the blank on every tape together may never be read
+        let blank = vec![self.blank()];
+        for state in self.states().iter().filter(|state| !self.is_halting_state(state)) {
+            for row in self.symbols().iter().filter(|row| !row.contains(&WILDCARD)) {
+                if self.find_all(state, row).is_empty() {
+                    lints.push(Lint::MissingRule { state: state.clone(), symbols: row.clone() });
+                }
+            }
+            if self.tapes() == 1 && !self.symbols().contains(&blank) && self.find_all(state, &blank).is_empty() {
+                lints.push(Lint::MissingRule { state: state.clone(), symbols: blank.clone() });
+            }
+        }
+        let halting = self.halting_states();
+        for state in self.states().iter().filter(|state| !halting.contains(state)) {
+            lints.push(Lint::NoHalt { state: state.clone() });
+        }
+        lints
+    }
+
+    /// every rule of the table with its column and row, column by column
+    fn rules(&self) -> impl Iterator<Item = (&RuleState, &Vec<char>, &Rule)> {
+        self.states().iter().flat_map(move |state| self.symbols().iter()
+            .flat_map(move |row| self.cell(state, row).iter().map(move |rule| (state, row, rule))))
+    }
+
+    fn reachable_states(&self) -> HashSet<RuleState> {
+        let mut reachable = HashSet::new();
+        let mut stack: Vec<RuleState> = self.states().first().cloned().into_iter().collect();
+        while let Some(state) = stack.pop() {
+            if !reachable.insert(state.clone()) {
+                continue;
+            }
+            for (_, _, rule) in self.rules().filter(|(from, _, _)| **from == state) {
+                // a halting rule ends the run, so its next state is never run
+                if !rule.is_terminal() {
+                    stack.push(rule.next_state.clone());
+                }
+            }
+        }
+        reachable
+    }
+
+    /// states from which the machine may halt: by a rule which stops every head, or by entering an accept or a reject state
+    fn halting_states(&self) -> HashSet<RuleState> {
+        let mut halting: HashSet<RuleState> = self.states().iter().filter(|state| self.is_halting_state(state)).cloned().collect();
+        loop {
+            let found: Vec<RuleState> = self.rules()
+                .filter(|(state, _, rule)| !halting.contains(*state)
+                    && (rule.is_terminal() || self.is_halting_state(&rule.next_state) || halting.contains(&rule.next_state)))
+                .map(|(state, _, _)| state.clone())
+                .collect();
+            if found.is_empty() {
+                return halting;
+            }
+            halting.extend(found);
+        }
+    }
+}
+
+impl Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Lint::UnreachableState { state } => write!(f, "State \"{}\" can't be reached from the initial state", state),
+            Lint::UnknownState { state, symbols, next_state } => write!(f, "Rule for state \"{}\" and symbol \"{}\" goes to the unknown state \"{}\"", state, symbols.iter().collect::<String>(), next_state),
+            Lint::UnknownSymbol { state, symbols, symbol } => write!(f, "Rule for state \"{}\" and symbol \"{}\" writes \"{}\", which is not in the alphabet", state, symbols.iter().collect::<String>(), symbol),
+            Lint::MissingRule { state, symbols } => write!(f, "Rule for state \"{}\" and symbol \"{}\" is missing", state, symbols.iter().collect::<String>()),
+            Lint::NoHalt { state } => write!(f, "State \"{}\" never leads to a halt", state),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::lint::Lint;
+    use crate::ruleset::Ruleset;
+
+    fn check(rules: &str) -> Vec<String> {
+        rules.parse::<Ruleset>().unwrap().check().iter().map(Lint::to_string).collect()
+    }
+
+    #[test]
+    fn test_check_clean() {
+        let rules = "\
+accept: 2
+reject: 3
+|   | 0   | 1   |
+|:-:|:-:  |:-:  |
+| a | a>1 | a>0 |
+| _ | _>2 | _>3 |";
+        assert_eq!(check(rules), Vec::<String>::new());
+        // the wildcard row fills the cells of the state
+        assert_eq!(check("|   | 0   |\n|:-:|:-:|\n| b | b!0 |\n| * | *>0 |"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_check() {
+        let rules = "\
+|   | 0   | 1   | 2   | 3   |
+|:-:|:-:  |:-:  |:-:  |:-:  |
+| a | b>1 | a>4 | a>2 | a!3 |
+| b | b!0 | x<0 | b>2 |     |
+| _ | _!0 | _<0 |     | _!3 |";
+        assert_eq!(check(rules), vec![
+            "State \"2\" can't be reached from the initial state",
+            "State \"3\" can't be reached from the initial state",
+            "Rule for state \"1\" and symbol \"a\" goes to the unknown state \"4\"",
+            "Rule for state \"1\" and symbol \"b\" writes \"x\", which is not in the alphabet",
+            "Rule for state \"2\" and symbol \"_\" is missing",
+            "Rule for state \"3\" and symbol \"b\" is missing",
+            "State \"2\" never leads to a halt",
+        ]);
+    }
+
+    #[test]
+    fn test_check_multi_tape() {
+        let rules = "\
+|    | 0     | 1     |
+|:-:|:-:|:-:|
+| a_ | a>a>0 | *!*!1 |
+| __ | _<_<1 |       |";
+        assert_eq!(check(rules), vec!["Rule for state \"1\" and symbol \"__\" is missing"]);
+    }
+
+    #[test]
+    fn test_check_blank() {
+        // the blank has no row, but the head reads it past the end of the input
+        assert_eq!(check("|   | 0   |\n|:-:|:-:|\n| a | a>0 |"), vec![
+            "Rule for state \"0\" and symbol \"_\" is missing",
+            "State \"0\" never leads to a halt",
+        ]);
+        assert_eq!(check("blank: .\n|   | 0   |\n|:-:|:-:|\n| a | a!0 |\n| _ | _!0 |"), vec!["Rule for state \"0\" and symbol \".\" is missing"]);
+        assert_eq!(check("|    | 0     |\n|:-:|:-:|\n| a_ | *!*!0 |"), Vec::<String>::new());
+    }
+}
@@ -0,0 +1,376 @@
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+use std::fmt::Display;
+use std::str::FromStr;
+use crate::composition::unique;
+use crate::import::{state_name, Bridges};
+use crate::rule::{Move, Rule, RuleState, WILDCARD};
+use crate::ruleset::Ruleset;
+use crate::tape::{Tape, SPACE};
+use crate::turing::Turing;
+
+/// A single-tape machine written one rule per line, like in the simulator of Anthony Morphett:
+/// `<state> <read> <write> <direction> <next state>`, with comments after `;`:
+/// ```text
+/// ; replaces "a" with "b" up to the blank
+/// 0 a b r 0
+/// 0 _ _ * halt
+/// ```
+/// The direction is `l`, `r` or `*` to stay. `*` reads any symbol, writes back the read one,
+/// and as the state or the next state stands for every state or the same state. `_` is the blank.
+/// The machine starts in the state `0` and halts in the states whose names start with `halt`.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MorphettMachine {
+    ruleset: Ruleset,
+    state: RuleState,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MorphettError {
+    /// the rules are written for a single tape
+    MultiTape { tapes: usize },
+}
+
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MorphettParseError {
+    /// the line is not a rule
+    InvalidLine { line: usize, text: String },
+    /// the symbol is not a single character
+    InvalidSymbol { line: usize, symbol: String },
+    /// the name of the state clashes with another one
+    InvalidState { line: usize, state: String },
+    /// the text has no rules
+    Empty,
+}
+
+impl MorphettMachine {
+    /// the machine starting in `state`. The ruleset must be single-tape.
+    pub fn new(ruleset: Ruleset, state: impl Into<RuleState>) -> Result<MorphettMachine, MorphettError> {
+        if ruleset.tapes() != 1 {
+            return Err(MorphettError::MultiTape { tapes: ruleset.tapes() });
+        }
+        Ok(MorphettMachine { ruleset, state: state.into() })
+    }
+
+    pub fn ruleset(&self) -> &Ruleset {
+        &self.ruleset
+    }
+
+    pub fn state(&self) -> RuleState {
+        self.state.clone()
+    }
+
+    /// the rules keep no input, the machine starts on the given tape
+    pub fn into_turing(self, tape: Tape) -> Turing {
+        Turing::new(tape, self.state, self.ruleset)
+    }
+}
+
+/// the states whose names start with `halt` halt the machine
+fn is_halt(name: &str) -> bool {
+    name.starts_with("halt")
+}
+
+impl Display for MorphettMachine {
+    /// writes the rules of every state in the order of the table, the rows with wildcards after the others.
+    /// The accept states are written as `halt-accept` and the reject states as `halt-reject`, and their rules are left out.
+    /// A rule which stops the head and halts the machine stays and goes to `halt_{state}`, unless its next state already halts.
+    /// The initial state is written first and named `0`, another state named `0` is renamed.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let ruleset = &self.ruleset;
+        let mut taken: HashSet<RuleState> = ruleset.states().iter().cloned().collect();
+        taken.insert(self.state.clone());
+        let mut names: HashMap<RuleState, String> = HashMap::new();
+        let zero: RuleState = 0.into();
+        if self.state != zero {
+            names.insert(self.state.clone(), zero.to_string());
+            names.insert(zero, unique("q0".to_string(), &mut taken).to_string());
+        }
+        for state in ruleset.accept_states() {
+            names.insert(state.clone(), "halt-accept".to_string());
+        }
+        for state in ruleset.reject_states() {
+            names.insert(state.clone(), "halt-reject".to_string());
+        }
+        let name = |state: &RuleState| names.get(state).cloned().unwrap_or_else(|| state.to_string());
+        let mut halts: HashMap<RuleState, RuleState> = HashMap::new();
+        let mut halt = |state: &RuleState| -> String {
+            if ruleset.is_halting_state(state) || is_halt(state.name()) {
+                name(state)
+            } else {
+                halts.entry(state.clone()).or_insert_with(|| unique(format!("halt_{}", state), &mut taken)).to_string()
+            }
+        };
+        let symbol = |symbol: char| if symbol == ruleset.blank() { SPACE } else { symbol };
+        let mut states: Vec<&RuleState> = vec![&self.state];
+        states.extend(ruleset.states().iter().filter(|state| **state != self.state));
+        let mut rows: Vec<&Vec<char>> = ruleset.symbols().iter().filter(|row| !row.contains(&WILDCARD)).collect();
+        rows.extend(ruleset.symbols().iter().filter(|row| row.contains(&WILDCARD)));
+        for state in states.into_iter().filter(|state| !ruleset.is_halting_state(state)) {
+            for row in &rows {
+                for rule in ruleset.cell(state, row) {
+                    let keeps = rule.write() == WILDCARD || rule.write() == row[0];
+                    // the halting states halt without rules
+                    if rule.is_terminal() && keeps && rule.next_state == *state && is_halt(state.name()) {
+                        continue;
+                    }
+                    let (direction, next) = match rule.mov() {
+                        Move::Left => ("l", name(&rule.next_state)),
+                        Move::Right => ("r", name(&rule.next_state)),
+                        Move::Stop => ("*", halt(&rule.next_state)),
+                    };
+                    writeln!(f, "{} {} {} {} {}", name(state), symbol(row[0]), symbol(rule.write()), direction, next)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for MorphettMachine {
+    type Err = MorphettParseError;
+
+    /// reads the rules. `halt-accept` becomes the accept state and `halt-reject` the reject state, the other halting states
+    /// get a `*` row which halts the machine. The machine starts in the state `0`, or in the first state when there is no `0`.
+    /// A rule which stays goes through a bridge to its next state, unless the next state halts.
+    /// Breakpoints, a `!` after the rule, are ignored.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut names: HashMap<RuleState, String> = HashMap::new();
+        let mut states: Vec<RuleState> = Vec::new();
+        let mut lines = Vec::new();
+        for (i, text) in s.lines().enumerate() {
+            let line = i + 1;
+            let rule = text.split(';').next().unwrap_or("");
+            let words: Vec<&str> = rule.split_whitespace().collect();
+            let [state, read, write, direction, next, rest @ ..] = words.as_slice() else {
+                if words.is_empty() {
+                    continue;
+                }
+                return Err(MorphettParseError::InvalidLine { line, text: text.to_string() });
+            };
+            if !matches!(rest, [] | ["!"]) {
+                return Err(MorphettParseError::InvalidLine { line, text: text.to_string() });
+            }
+            let mut state_of = |name: &str| -> Result<Option<RuleState>, MorphettParseError> {
+                if name == "*" {
+                    return Ok(None);
+                }
+                let invalid = || MorphettParseError::InvalidState { line, state: name.to_string() };
+                let state = state_name(name).ok_or_else(invalid)?;
+                match names.get(&state) {
+                    Some(original) if original != name => return Err(invalid()),
+                    Some(_) => (),
+                    None => {
+                        names.insert(state.clone(), name.to_string());
+                        states.push(state.clone());
+                    }
+                }
+                Ok(Some(state))
+            };
+            let state = state_of(state)?;
+            let next = state_of(next)?;
+            let symbol = |symbol: &str| {
+                let mut chars = symbol.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => Ok(c),
+                    _ => Err(MorphettParseError::InvalidSymbol { line, symbol: symbol.to_string() }),
+                }
+            };
+            let direction = match direction.to_lowercase().as_str() {
+                "l" => Some(Move::Left),
+                "r" => Some(Move::Right),
+                "*" => None,
+                _ => return Err(MorphettParseError::InvalidLine { line, text: text.to_string() }),
+            };
+            lines.push((state, symbol(read)?, symbol(write)?, direction, next));
+        }
+        if lines.is_empty() {
+            return Err(MorphettParseError::Empty);
+        }
+        let halts = |state: &RuleState| is_halt(&names[state]);
+
+        let mut rules: HashMap<RuleState, HashMap<Vec<char>, Vec<Rule>>> = HashMap::new();
+        let mut rows: Vec<Vec<char>> = Vec::new();
+        let mut bridges = Bridges::default();
+        let mut taken: HashSet<RuleState> = states.iter().cloned().collect();
+        let mut rule = |state: &RuleState, write: char, direction: Option<Move>, next: &Option<RuleState>| {
+            let next = next.clone().unwrap_or_else(|| state.clone());
+            match direction {
+                Some(mov) => Rule::new(write, mov, next),
+                None if halts(&next) => Rule::new(write, Move::Stop, next),
+                None => bridges.rule(vec![write], &next, &mut taken),
+            }
+        };
+        for (state, read, write, direction, next) in &lines {
+            if let Some(state) = state {
+                let rule = rule(state, *write, *direction, next);
+                rules.entry(state.clone()).or_default().entry(vec![*read]).or_default().push(rule);
+            }
+            if !rows.contains(&vec![*read]) {
+                rows.push(vec![*read]);
+            }
+        }
+        // the rules of every state, unless the state has its own rules for the symbol
+        for (_, read, write, direction, next) in lines.iter().filter(|(state, ..)| state.is_none()) {
+            for state in states.iter().filter(|state| !halts(state)) {
+                if !rules.get(state).is_some_and(|cells| cells.contains_key(&vec![*read])) {
+                    let rule = rule(state, *write, *direction, next);
+                    rules.entry(state.clone()).or_default().insert(vec![*read], vec![rule]);
+                }
+            }
+        }
+
+        let mut accept_states = Vec::new();
+        let mut reject_states = Vec::new();
+        for state in states.iter().filter(|state| halts(state)) {
+            match state.name() {
+                "halt_accept" => accept_states.push(state.clone()),
+                "halt_reject" => reject_states.push(state.clone()),
+                _ => {
+                    rules.entry(state.clone()).or_default().insert(vec![WILDCARD], vec![Rule::new(WILDCARD, Move::Stop, state.clone())]);
+                }
+            }
+        }
+        bridges.add(&mut rules, &mut rows, &mut states, 1);
+        if rules.values().any(|cells| cells.contains_key(&vec![WILDCARD])) && !rows.contains(&vec![WILDCARD]) {
+            rows.push(vec![WILDCARD]);
+        }
+        // the wildcard row goes after the symbols
+        rows.sort_by_key(|row| row[0] == WILDCARD);
+        let initial: RuleState = 0.into();
+        let initial = if states.contains(&initial) { initial } else { states[0].clone() };
+        states.sort_by_key(|state| *state != initial);
+        let mut ruleset = Ruleset::new(rules, rows, states);
+        ruleset.set_accept_states(accept_states);
+        ruleset.set_reject_states(reject_states);
+        Ok(MorphettMachine { ruleset, state: initial })
+    }
+}
+
+impl Display for MorphettError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MorphettError::MultiTape { tapes } => write!(f, "Only single-tape machines can be written one rule per line, the ruleset has {} tapes", tapes),
+        }
+    }
+}
+
+impl Display for MorphettParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MorphettParseError::InvalidLine { line, text } => write!(f, "Invalid rule at line {}: {}", line, text),
+            MorphettParseError::InvalidSymbol { line, symbol } => write!(f, "Invalid symbol at line {}: {}", line, symbol),
+            MorphettParseError::InvalidState { line, state } => write!(f, "Invalid state at line {}: {}", line, state),
+            MorphettParseError::Empty => write!(f, "No rules found"),
+        }
+    }
+}
+
+impl Error for MorphettError {}
+impl Error for MorphettParseError {}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+    use crate::morphett::{MorphettError, MorphettMachine, MorphettParseError};
+    use crate::outcome::{Outcome, Verdict};
+    use crate::ruleset::Ruleset;
+    use crate::tape::Tape;
+
+    // accepts the words of "a" and "b" which end with "b"
+    const ENDS_WITH_B: &str = "\
+; go to the end of the word
+0 * * r 0
+0 _ _ l check   ; the last symbol
+check b b * halt-accept
+check * * * halt-reject
+";
+
+    #[test]
+    fn test_morphett_from_str() {
+        let machine = MorphettMachine::from_str(ENDS_WITH_B).unwrap();
+        assert_eq!(machine.ruleset().to_string(), Ruleset::from_str("\
+accept: halt_accept
+reject: halt_reject
+|   | 0   | check | halt_accept | halt_reject |
+|:-:|:-:  |:-:    |:-:          |:-:          |
+| _ | _<check |   |             |             |
+| b |     | b!halt_accept |     |             |
+| * | *>0 | *!halt_reject |     |             |").unwrap().to_string());
+        let mut turing = machine.clone().into_turing(Tape::new("aab".chars().collect(), 0, 0));
+        turing.run(100).unwrap();
+        assert_eq!(turing.verdict(), Some(Verdict::Accept));
+        let mut turing = machine.into_turing(Tape::new("aba".chars().collect(), 0, 0));
+        turing.run(100).unwrap();
+        assert_eq!(turing.verdict(), Some(Verdict::Reject));
+    }
+
+    #[test]
+    fn test_morphett_stay_and_halt() {
+        // stays to go to "back", a rule for every state and a plain halting state
+        let machine = MorphettMachine::from_str("\
+start a x * back
+back x y r start
+* _ _ * halt
+").unwrap();
+        assert_eq!(machine.state(), "start".parse().unwrap());
+        assert_eq!(machine.ruleset().to_string(), Ruleset::from_str("\
+|   | start | back | halt | back_enter |
+|:-:|:-:    |:-:   |:-:   |:-:        |
+| a | x>back_enter |  |   |            |
+| x |       | y>start |   |            |
+| _ | _!halt | _!halt |  |            |
+| * |       |      | *!halt | *<back   |").unwrap().to_string());
+        let mut turing = machine.into_turing(Tape::new("aa".chars().collect(), 0, 0));
+        assert_eq!(turing.run(100).unwrap().outcome(), &Outcome::Halted);
+        assert_eq!(turing.state(), "halt".parse().unwrap());
+        assert_eq!(turing.tape().to_string(), " y  y [_]");
+    }
+
+    #[test]
+    fn test_morphett_display() {
+        let machine = MorphettMachine::from_str(ENDS_WITH_B).unwrap();
+        assert_eq!(machine.to_string(), "\
+0 _ _ l check
+0 * * r 0
+check b b * halt-accept
+check * * * halt-reject
+");
+        assert_eq!(MorphettMachine::from_str(&machine.to_string()).unwrap().ruleset(), machine.ruleset());
+
+        // the terminal rules halt in a new state, the initial state is named 0 and the state 0 is renamed
+        let ruleset = Ruleset::from_str("\
+|   | 0   | 1   |
+|:-:|:-:  |:-:  |
+| a | a>1 | b!0 |
+| _ | _!0 |     |").unwrap();
+        let text = MorphettMachine::new(ruleset, 1).unwrap().to_string();
+        assert_eq!(text, "0 a b * halt_0\nq0 a a r 0\nq0 _ _ * halt_0\n");
+        let mut turing = MorphettMachine::from_str(&text).unwrap().into_turing(Tape::new(vec!['a'], 0, 0));
+        assert_eq!(turing.run(100).unwrap().outcome(), &Outcome::Halted);
+        assert_eq!(turing.state(), "halt_0".parse().unwrap());
+        assert_eq!(turing.tape().to_string(), "[b]");
+    }
+
+    #[test]
+    fn test_morphett_errors() {
+        assert_eq!(MorphettMachine::from_str("; nothing").err(), Some(MorphettParseError::Empty));
+        assert_eq!(MorphettMachine::from_str("0 a b r").err(), Some(MorphettParseError::InvalidLine { line: 1, text: "0 a b r".to_string() }));
+        assert_eq!(MorphettMachine::from_str("0 a b x 1").err(), Some(MorphettParseError::InvalidLine { line: 1, text: "0 a b x 1".to_string() }));
+        assert_eq!(MorphettMachine::from_str("\n0 ab b r 1").err(), Some(MorphettParseError::InvalidSymbol { line: 2, symbol: "ab".to_string() }));
+        assert_eq!(MorphettMachine::from_str("0 a b r a-b\na-b a b r a_b").err(), Some(MorphettParseError::InvalidState { line: 2, state: "a_b".to_string() }));
+        assert!(MorphettMachine::from_str("0 a b r 1 !").is_ok());
+        assert_eq!(MorphettMachine::from_str("0 a b r 1 ! x").err(), Some(MorphettParseError::InvalidLine { line: 1, text: "0 a b r 1 ! x".to_string() }));
+        // the directions are read in any case
+        assert_eq!(MorphettMachine::from_str("0 a b R 1").unwrap().ruleset(), MorphettMachine::from_str("0 a b r 1").unwrap().ruleset());
+        let two_tapes = Ruleset::from_str("\
+|    | 0     |
+|:-:|:-:    |
+| a_ | a>a>0 |").unwrap();
+        assert_eq!(MorphettMachine::new(two_tapes, 0).err(), Some(MorphettError::MultiTape { tapes: 2 }));
+    }
+}
@@ -0,0 +1,33 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use crate::transition::Transition;
+use crate::turing::{Turing, TuringError};
+
+/// Receives notifications about the execution of a machine, see `Turing::add_observer`.
+/// Every callback does nothing by default.
+pub trait Observer {
+    /// the transition is found, but not applied yet
+    fn before_step(&mut self, _turing: &Turing, _transition: &Transition) {}
+    /// the transition is applied, `turing` is in the new configuration
+    fn after_step(&mut self, _turing: &Turing, _transition: &Transition) {}
+    /// the last step halted the machine
+    fn on_halt(&mut self, _turing: &Turing) {}
+    /// the next transition can't be found
+    fn on_error(&mut self, _turing: &Turing, _error: &TuringError) {}
+}
+
+/// lets the owner read the collected data after the run
+impl<T: Observer> Observer for Rc<RefCell<T>> {
+    fn before_step(&mut self, turing: &Turing, transition: &Transition) {
+        self.borrow_mut().before_step(turing, transition)
+    }
+    fn after_step(&mut self, turing: &Turing, transition: &Transition) {
+        self.borrow_mut().after_step(turing, transition)
+    }
+    fn on_halt(&mut self, turing: &Turing) {
+        self.borrow_mut().on_halt(turing)
+    }
+    fn on_error(&mut self, turing: &Turing, error: &TuringError) {
+        self.borrow_mut().on_error(turing, error)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,62 @@
+use crate::breakpoint::Breakpoint;
+use crate::rule::RuleState;
+
+/// How a run of the machine ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Outcome {
+    /// a terminal rule was applied
+    Halted,
+    /// the machine is still running after the step limit
+    StepLimitReached,
+    /// there is no rule for the state and the symbols under the heads
+    MissingRule {
+        state: RuleState,
+        symbols: Vec<char>,
+    },
+    /// the configuration after the last step, or before the first one, matches the breakpoint
+    BreakpointHit {
+        breakpoint: Breakpoint,
+    },
+    /// the configuration seen at step `first_seen` came back at step `repeated_at`, so the machine never halts
+    LoopDetected {
+        first_seen: usize,
+        repeated_at: usize,
+    },
+}
+
+/// Verdict of a machine which recognizes a language, see `Ruleset::accept_states`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Verdict {
+    Accept,
+    Reject,
+}
+
+/// Result of `Turing::run`: the outcome, the number of steps made by the run and the verdict of the machine.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Run {
+    outcome: Outcome,
+    steps: usize,
+    verdict: Option<Verdict>,
+}
+
+impl Run {
+    pub(crate) fn new(outcome: Outcome, steps: usize, verdict: Option<Verdict>) -> Run {
+        Run { outcome, steps, verdict }
+    }
+
+    /// `None` while the machine is running or when its ruleset has neither accept nor reject states
+    pub fn verdict(&self) -> Option<Verdict> {
+        self.verdict
+    }
+
+    pub fn outcome(&self) -> &Outcome {
+        &self.outcome
+    }
+
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+}
\ No newline at end of file
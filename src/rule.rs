@@ -1,43 +1,135 @@
 use fmt::Display;
+use std::cmp::Ordering;
 use std::fmt;
 use std::str::FromStr;
+use std::sync::Arc;
+
+/// Name of a state, like `0`, `carry` or `q_start`: letters, digits and `_`.
+/// Numeric names are ordered as numbers and go before the other names.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct RuleState(Arc<str>);
+
+impl RuleState {
+    pub fn name(&self) -> &str {
+        &self.0
+    }
+
+    pub(crate) fn number(&self) -> Option<u64> {
+        self.0.parse().ok()
+    }
+}
+
+impl From<u32> for RuleState {
+    fn from(number: u32) -> Self {
+        RuleState(number.to_string().into())
+    }
+}
+
+impl FromStr for RuleState {
+    type Err = RuleParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() || !s.chars().all(|c| c.is_alphanumeric() || c == '_') {
+            return Err(RuleParseError::InvalidState);
+        }
+        // a numeric name is a number, so `00` and `0` are the same state
+        if s.chars().all(|c| c.is_ascii_digit()) {
+            let number = s.trim_start_matches('0');
+            return Ok(RuleState(if number.is_empty() { "0" } else { number }.into()));
+        }
+        Ok(RuleState(s.into()))
+    }
+}
+
+impl Display for RuleState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Ord for RuleState {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.number(), other.number()) {
+            (Some(a), Some(b)) => a.cmp(&b).then_with(|| self.0.cmp(&other.0)),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => self.0.cmp(&other.0),
+        }
+    }
+}
+
+impl PartialOrd for RuleState {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
 
-pub type RuleState = u32;
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Move {
     Right,
     Left,
     Stop,
 }
 #[derive(Debug, PartialEq, Eq)]
+#[allow(clippy::enum_variant_names)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum RuleParseError {
     InvalidRule,
     InvalidMove,
     InvalidState,
 }
-#[derive(Copy, Clone, PartialEq, Debug, Eq)]
+/// matches any symbol in the symbol column of a ruleset, and keeps the symbol under the head when written by a rule
+pub const WILDCARD: char = '*';
+
+/// A rule of a k-tape machine: one write and one move for every tape, plus the next state.
+/// Single-tape rules are the special case k = 1. A write of `WILDCARD` leaves the cell as it is.
+#[derive(Clone, PartialEq, Debug, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Rule {
-    pub(crate) write: char,
-    pub(crate) mov: Move,
+    pub(crate) writes: Vec<char>,
+    pub(crate) moves: Vec<Move>,
     pub(crate) next_state: RuleState,
 }
 impl Rule {
-    pub fn new(write: char, mov: Move, next_state: RuleState) -> Rule {
+    pub fn new(write: char, mov: Move, next_state: impl Into<RuleState>) -> Rule {
+        Rule::with_tapes(vec![(write, mov)], next_state)
+    }
+
+    /// creates a rule for a multi-tape machine from the write and the move of every tape
+    pub fn with_tapes(actions: Vec<(char, Move)>, next_state: impl Into<RuleState>) -> Rule {
+        let (writes, moves) = actions.into_iter().unzip();
         Rule {
-            write,
-            mov,
-            next_state,
+            writes,
+            moves,
+            next_state: next_state.into(),
         }
     }
 
+    /// symbol written to the first tape
     pub fn write(&self) -> char {
-        self.write
+        self.writes[0]
     }
+    /// move of the first tape head
     pub fn mov(&self) -> Move {
-        self.mov
+        self.moves[0]
+    }
+    pub fn writes(&self) -> &[char] {
+        &self.writes
+    }
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
     }
     pub fn next_state(&self) -> RuleState {
-        self.next_state
+        self.next_state.clone()
+    }
+    /// number of tapes the rule is written for
+    pub fn tapes(&self) -> usize {
+        self.writes.len()
+    }
+    /// the rule halts the machine when every head stops
+    pub fn is_terminal(&self) -> bool {
+        self.moves.iter().all(Move::is_terminal)
     }
 }
 
@@ -52,7 +144,16 @@ impl Display for Move {
 }
 impl Move {
     pub fn is_terminal(&self) -> bool {
-        return *self == Move::Stop;
+        *self == Move::Stop
+    }
+
+    fn from_char(c: char) -> Option<Move> {
+        match c {
+            '>' => Some(Move::Right),
+            '<' => Some(Move::Left),
+            '!' => Some(Move::Stop),
+            _ => None,
+        }
     }
 }
 
@@ -68,29 +169,35 @@ impl Display for RuleParseError {
 impl FromStr for Rule {
     type Err = RuleParseError;
 
+    /// format is `{write}{move}` for every tape followed by the next state, e.g. `a>1`, `a>b<1` or `a>carry`.
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut chars = s.chars();
         let write = chars.next().ok_or(RuleParseError::InvalidRule)?;
-        let mov = match chars.next().ok_or(RuleParseError::InvalidMove)? {
-            '>' => Move::Right,
-            '<' => Move::Left,
-            '!' => Move::Stop,
-            _ => return Err(RuleParseError::InvalidRule),
-        };
-        let next_state = chars.as_str().parse().map_err(|_| RuleParseError::InvalidState)?;
-        Ok(Rule::new(write, mov, next_state))
+        let mov = Move::from_char(chars.next().ok_or(RuleParseError::InvalidMove)?).ok_or(RuleParseError::InvalidRule)?;
+        let mut actions = vec![(write, mov)];
+        // every next pair of `{write}{move}` belongs to the next tape
+        let mut lookahead = chars.clone();
+        while let (Some(write), Some(mov)) = (lookahead.next(), lookahead.next().and_then(Move::from_char)) {
+            actions.push((write, mov));
+            chars = lookahead.clone();
+        }
+        let next_state: RuleState = chars.as_str().parse()?;
+        Ok(Rule::with_tapes(actions, next_state))
     }
 }
 
 impl Display for Rule {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}{}{}", self.write, self.mov, self.next_state)
+        for (write, mov) in self.writes.iter().zip(self.moves.iter()) {
+            write!(f, "{}{}", write, mov)?;
+        }
+        write!(f, "{}", self.next_state)
     }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::{Move, Rule};
+    use crate::{Move, Rule, RuleState};
     use crate::rule::RuleParseError;
 
     #[test]
@@ -107,6 +214,32 @@ mod test {
         assert_eq!("a< 123".parse::<Rule>().unwrap_err(), RuleParseError::InvalidState);
     }
 
+    #[test]
+    fn test_multi_tape_rule_from_str() {
+        assert_eq!("a>b<1".parse::<Rule>().unwrap(), Rule::with_tapes(vec![('a', Move::Right), ('b', Move::Left)], 1));
+        assert_eq!("<<>!_>12".parse::<Rule>().unwrap(), Rule::with_tapes(vec![('<', Move::Left), ('>', Move::Stop), ('_', Move::Right)], 12));
+        assert_eq!("a>b<".parse::<Rule>().unwrap_err(), RuleParseError::InvalidState);
+        assert_eq!(Rule::with_tapes(vec![('a', Move::Right), ('b', Move::Stop)], 3).to_string(), "a>b!3");
+        assert!(Rule::with_tapes(vec![('a', Move::Stop), ('b', Move::Stop)], 3).is_terminal());
+        assert!(!Rule::with_tapes(vec![('a', Move::Stop), ('b', Move::Left)], 3).is_terminal());
+    }
+
+    #[test]
+    fn test_named_states() {
+        let state = |name: &str| name.parse::<RuleState>().unwrap();
+        assert_eq!("a>carry".parse::<Rule>().unwrap(), Rule::new('a', Move::Right, state("carry")));
+        assert_eq!("a>b<q_start".parse::<Rule>().unwrap(), Rule::with_tapes(vec![('a', Move::Right), ('b', Move::Left)], state("q_start")));
+        assert_eq!("a>b-c".parse::<Rule>().unwrap_err(), RuleParseError::InvalidState);
+        assert_eq!(Rule::new('a', Move::Stop, state("done")).to_string(), "a!done");
+        assert_eq!(state("7"), RuleState::from(7));
+        assert_eq!(state("007"), RuleState::from(7));
+        assert_eq!(state("00").to_string(), "0");
+        assert_ne!(state("q00"), state("q0"));
+        let mut states = ["carry", "10", "2", "done", "0"].map(state);
+        states.sort();
+        assert_eq!(states.map(|s| s.to_string()), ["0", "2", "10", "carry", "done"]);
+    }
+
     #[test]
     fn test_display() {
         assert_eq!(format!("{}", Rule::new('a', Move::Right, 1)), "a>1");
@@ -3,45 +3,112 @@ use std::error::Error;
 use std::fmt;
 use std::fmt::Display;
 use std::str::FromStr;
-use crate::rule::{Rule, RuleState};
+use crate::rule::{Rule, RuleState, WILDCARD};
+use crate::tape::SPACE;
 
 #[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum RulesetError {
     RuleNotFound {
         state: RuleState,
-        symbol: char,
+        symbols: Vec<char>,
     },
+    /// a deterministic lookup found a cell with several rules of a nondeterministic machine
+    AmbiguousRule {
+        state: RuleState,
+        symbols: Vec<char>,
+    },
+    /// the table has no states, so the machine has no initial state
+    NoStates,
 }
 #[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum RulesetParseError {
     InvalidRuleset,
     InvalidState {state: String},
     InvalidSymbol { row: usize},
     DuplicateState {state: RuleState},
-    DuplicateSymbol {symbol: char},
+    DuplicateSymbol {symbols: Vec<char>},
     InvalidFormat { row: usize, col: usize},
     InvalidRule { row: usize, col: usize, format: String},
+    InvalidHeader { line: String },
 }
+/// Rules of a k-tape machine. Every rule is looked up by the state and the tuple of symbols under the k heads.
+/// A cell of a nondeterministic machine holds several rules.
 #[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "crate::serialization::RulesetTable", into = "crate::serialization::RulesetTable"))]
 pub struct Ruleset {
-    rules: HashMap<RuleState, HashMap<char, Rule>>,
+    rules: HashMap<RuleState, HashMap<Vec<char>, Vec<Rule>>>,
     alphabet: Vec<char>,
-    states: Vec<RuleState>
+    symbols: Vec<Vec<char>>,
+    states: Vec<RuleState>,
+    tapes: usize,
+    accept_states: Vec<RuleState>,
+    reject_states: Vec<RuleState>,
+    blank: char,
 }
 
 impl Ruleset {
     pub fn find(&self, state: &RuleState, symbol: &char) -> Result<Rule, RulesetError> {
+        self.find_symbols(state, &[*symbol])
+    }
+
+    /// finds the rule for the symbols read by every head, the first symbol is read from the first tape.
+    pub fn find_symbols(&self, state: &RuleState, symbols: &[char]) -> Result<Rule, RulesetError> {
+        match self.find_all(state, symbols) {
+            [] => Err(RulesetError::RuleNotFound {state: state.clone(), symbols: symbols.to_vec() }),
+            [rule] => Ok(rule.clone()),
+            _ => Err(RulesetError::AmbiguousRule {state: state.clone(), symbols: symbols.to_vec() }),
+        }
+    }
+
+    /// finds every rule of the cell. A deterministic machine has at most one rule in a cell.
+    /// When the state has no cell for the symbols, the rules of the matching row with wildcards are used, see `matching_row`.
+    pub fn find_all(&self, state: &RuleState, symbols: &[char]) -> &[Rule] {
+        self.matching_row(state, symbols).map_or(&[], |row| self.cell(state, row))
+    }
+
+    /// rules written in the cell of the state and the row, without the wildcard fallback
+    pub fn cell(&self, state: &RuleState, row: &[char]) -> &[Rule] {
         self.rules
             .get(state)
-            .and_then(|m| m.get(symbol))
-            .cloned()
-            .ok_or(RulesetError::RuleNotFound {state: *state, symbol: *symbol })
+            .and_then(|m| m.get(row))
+            .map_or(&[], |rules| rules.as_slice())
+    }
+
+    /// the row of the table which holds the rules of the state for the symbols: the row of the symbols themselves
+    /// or else the row with the fewest wildcards `*` among the rows matching the symbols, like `*` or `a*`,
+    /// where the state has a cell. Rows with as many wildcards are taken in the order of the table.
+    pub fn matching_row(&self, state: &RuleState, symbols: &[char]) -> Option<&Vec<char>> {
+        let cells = self.rules.get(state)?;
+        if let Some((row, _)) = cells.get_key_value(symbols) {
+            return Some(row);
+        }
+        self.symbols.iter()
+            .filter(|row| cells.contains_key(*row) && row.len() == symbols.len())
+            .filter(|row| row.iter().zip(symbols).all(|(r, s)| *r == WILDCARD || r == s))
+            .min_by_key(|row| row.iter().filter(|r| **r == WILDCARD).count())
     }
-    pub fn new(rules: HashMap<RuleState, HashMap<char, Rule>>, alphabet: Vec<char>, states: Vec<RuleState>) -> Ruleset {
+
+    /// `symbols` are the rows of the table: a tuple with one symbol per tape.
+    /// The number of tapes is taken from the first row, a ruleset without rows is a single-tape one.
+    pub fn new(rules: HashMap<RuleState, HashMap<Vec<char>, Vec<Rule>>>, symbols: Vec<Vec<char>>, states: Vec<RuleState>) -> Ruleset {
+        let mut alphabet = Vec::new();
+        for symbol in symbols.iter().flatten() {
+            if *symbol != WILDCARD && !alphabet.contains(symbol) {
+                alphabet.push(*symbol);
+            }
+        }
         Ruleset {
             rules,
             alphabet,
+            tapes: symbols.first().map_or(1, |s| s.len()),
+            symbols,
             states,
+            accept_states: vec![],
+            reject_states: vec![],
+            blank: SPACE,
         }
     }
 
@@ -49,9 +116,65 @@ impl Ruleset {
         &self.states
     }
 
+    /// the machine starts in the state of the first column of the table
+    pub fn initial_state(&self) -> RuleState {
+        self.states.first().cloned().unwrap_or_else(|| 0.into())
+    }
+
+    /// every symbol that can be read from any tape
     pub fn alphabet(&self) -> &Vec<char> {
         &self.alphabet
     }
+
+    /// rows of the table, one symbol per tape
+    pub fn symbols(&self) -> &Vec<Vec<char>> {
+        &self.symbols
+    }
+
+    pub fn tapes(&self) -> usize {
+        self.tapes
+    }
+
+    /// the machine halts and accepts the input when it enters one of these states
+    pub fn accept_states(&self) -> &Vec<RuleState> {
+        &self.accept_states
+    }
+
+    /// the machine halts and rejects the input when it enters one of these states
+    pub fn reject_states(&self) -> &Vec<RuleState> {
+        &self.reject_states
+    }
+
+    pub fn set_accept_states(&mut self, states: Vec<RuleState>) {
+        self.accept_states = states;
+    }
+
+    pub fn set_reject_states(&mut self, states: Vec<RuleState>) {
+        self.reject_states = states;
+    }
+
+    /// the symbol of the empty cells, the tapes of the machine should be created with it
+    pub fn blank(&self) -> char {
+        self.blank
+    }
+
+    pub fn set_blank(&mut self, blank: char) {
+        self.blank = blank;
+    }
+
+    /// the machine halts when it enters an accept or a reject state
+    pub fn is_halting_state(&self, state: &RuleState) -> bool {
+        self.accept_states.contains(state) || self.reject_states.contains(state)
+    }
+
+    /// a ruleset with accept states recognizes a language: every other way to halt rejects the input
+    pub fn is_recognizer(&self) -> bool {
+        !self.accept_states.is_empty()
+    }
+
+    pub fn is_deterministic(&self) -> bool {
+        self.rules.values().flat_map(|m| m.values()).all(|rules| rules.len() <= 1)
+    }
 }
 
 impl Display for RulesetParseError {
@@ -61,9 +184,10 @@ impl Display for RulesetParseError {
             RulesetParseError::InvalidState {state} => write!(f, "Invalid state: {}", state),
             RulesetParseError::InvalidSymbol {row} => write!(f, "Invalid symbol in row {}", row),
             RulesetParseError::DuplicateState {state} => write!(f, "Duplicate state: {}", state),
-            RulesetParseError::DuplicateSymbol {symbol} => write!(f, "Duplicate symbol: {}", symbol),
+            RulesetParseError::DuplicateSymbol {symbols} => write!(f, "Duplicate symbol: {}", symbols.iter().collect::<String>()),
             RulesetParseError::InvalidFormat {row, col} => write!(f, "Invalid format in cell [{}, {}]", row, col),
             RulesetParseError::InvalidRule {row, col, format} => write!(f, "Invalid rule format in cell [{}, {}]: {}", row, col, format),
+            RulesetParseError::InvalidHeader {line} => write!(f, "Invalid header line: {}", line),
         }
     }
 }
@@ -71,7 +195,9 @@ impl Display for RulesetParseError {
 impl Display for RulesetError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            RulesetError::RuleNotFound {state, symbol} => write!(f, "Rule for state \"{}\" and symbol \"{}\" not found", state, symbol),
+            RulesetError::RuleNotFound {state, symbols} => write!(f, "Rule for state \"{}\" and symbol \"{}\" not found", state, symbols.iter().collect::<String>()),
+            RulesetError::AmbiguousRule {state, symbols} => write!(f, "Several rules for state \"{}\" and symbol \"{}\" in a deterministic run", state, symbols.iter().collect::<String>()),
+            RulesetError::NoStates => write!(f, "Ruleset has no states"),
         }
     }
 }
@@ -88,18 +214,63 @@ impl FromStr for Ruleset {
     /// | a | a>1 | a<2 | b>3 | a<0 |
     /// | b | _<1 | a>2 | a<3 | a>0 |
     /// | _ | b>2 | _<3 | _>0 | _<1 |
+    ///
+    /// a k-tape machine has k symbols in the first column and k pairs of `{write}{move}` in every cell:
+    /// |    | 0 | 1 |
+    /// |:-:|:-:|:-:|
+    /// | a_ | a>a>0 | a!_!1 |
+    /// | __ | _<_<1 | _!_!1 |
+    ///
+    /// an empty cell has no rule, the machine can't go on in its state and symbol.
+    /// A cell of a nondeterministic machine holds several rules separated by spaces:
+    /// |   | 0 | 1 |
+    /// |:-:|:-:|:-:|
+    /// | a | a>0 b>1 | a!1 |
+    ///
+    /// the wildcard `*` in the first column matches any symbol, so the row `*` holds the default rules of the states.
+    /// As a write, `*` keeps the symbol under the head. This state scans right up to the first `b`:
+    /// |   | 0   |
+    /// |:-:|:-:  |
+    /// | b | b!0 |
+    /// | * | *>0 |
+    ///
+    /// the table may be preceded by header lines which declare the accept and the reject states, and the blank symbol:
+    /// accept: 4, 5
+    /// reject: 6
+    /// blank: λ
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut rules = HashMap::new();
-        let mut alphabet = Vec::new();
-        let mut lines = s.lines().skip_while(|l| l.trim().is_empty());
-        let header = lines.next().ok_or(RulesetParseError::InvalidRuleset)?;
+        let mut symbols: Vec<Vec<char>> = Vec::new();
+        let mut accept_states = Vec::new();
+        let mut reject_states = Vec::new();
+        let mut blank = SPACE;
+        let mut lines = s.lines();
+        let header = loop {
+            let line = lines.next().ok_or(RulesetParseError::InvalidRuleset)?;
+            match line.trim() {
+                "" => continue,
+                l if l.starts_with('|') => break line,
+                l => match l.split_once(':').map(|(key, states)| (key.trim(), states)) {
+                    Some(("accept", states)) => accept_states = parse_states(states)?,
+                    Some(("reject", states)) => reject_states = parse_states(states)?,
+                    Some(("blank", symbol)) => {
+                        let mut chars = symbol.trim().chars();
+                        blank = match (chars.next(), chars.next()) {
+                            (Some(c), None) => c,
+                            _ => return Err(RulesetParseError::InvalidHeader { line: l.to_string() }),
+                        }
+                    }
+                    _ => return Err(RulesetParseError::InvalidHeader { line: l.to_string() }),
+                },
+            }
+        };
         let mut states = Vec::new();
-        for state in header.trim_end_matches(|c| c == '|').split('|').skip(2).map(|s| s.trim()).collect::<Vec<&str>>() {
-            let state = state.parse().map_err(|_| RulesetParseError::InvalidState { state: state.to_string() })?;
+        for state in header.trim_end_matches('|').split('|').skip(2).map(|s| s.trim()).collect::<Vec<&str>>() {
+            let state: RuleState = state.parse().map_err(|_| RulesetParseError::InvalidState { state: state.to_string() })?;
             if rules.contains_key(&state) {
                 return Err(RulesetParseError::DuplicateState {state});
             }
-            rules.insert(state, HashMap::new());
+            rules.insert(state.clone(), HashMap::new());
             states.push(state);
         }
         for (ind, line) in lines.enumerate() {
@@ -107,27 +278,54 @@ impl FromStr for Ruleset {
             if ind == 0 && (line.contains(":-") || line.contains("--")) {
                 continue;
             }
-            let mut cells = line.trim_end_matches(|c| c == '|').split('|').skip(1).take(states.len() + 1);
-            let symbol = cells
+            let mut cells = line.trim_end_matches('|').split('|').skip(1).take(states.len() + 1);
+            let symbol: Vec<char> = cells
                 .next()
                 .ok_or(RulesetParseError::InvalidFormat{ row: ind, col: 0})?
                 .trim()
-                .parse()
-                .map_err(|_| RulesetParseError::InvalidSymbol { row: ind} )?;
-            if alphabet.contains(&symbol) {
-                return Err(RulesetParseError::DuplicateSymbol { symbol });
+                .chars()
+                .collect();
+            // every row must have a symbol for every tape, the first row sets the number of tapes
+            if symbol.is_empty() || symbols.first().is_some_and(|first| first.len() != symbol.len()) {
+                return Err(RulesetParseError::InvalidSymbol { row: ind });
+            }
+            if symbols.contains(&symbol) {
+                return Err(RulesetParseError::DuplicateSymbol { symbols: symbol });
             }
-            alphabet.push(symbol);
+            symbols.push(symbol.clone());
             for (i, cell) in cells.enumerate() {
-                let state = states.get(i).ok_or_else(|| RulesetParseError::InvalidFormat { row: ind, col: i})?.clone();
-                let rule = cell.trim().parse().map_err(|_| RulesetParseError::InvalidRule { row: ind, col: i, format: cell.to_string() })?;
-                rules.get_mut(&state).unwrap().insert(symbol, rule);
+                let state = states.get(i).ok_or(RulesetParseError::InvalidFormat { row: ind, col: i})?;
+                let invalid_rule = || RulesetParseError::InvalidRule { row: ind, col: i, format: cell.to_string() };
+                let mut cell_rules = Vec::new();
+                for rule in cell.split_whitespace() {
+                    let rule: Rule = rule.parse().map_err(|_| invalid_rule())?;
+                    if rule.tapes() != symbol.len() {
+                        return Err(invalid_rule());
+                    }
+                    cell_rules.push(rule);
+                }
+                // an empty cell has no rule, like the cells missing at the end of a row
+                if !cell_rules.is_empty() {
+                    rules.get_mut(state).unwrap().insert(symbol.clone(), cell_rules);
+                }
             }
         }
-        Ok(Ruleset::new(rules, alphabet, states))
+        let mut ruleset = Ruleset::new(rules, symbols, states);
+        ruleset.set_accept_states(accept_states);
+        ruleset.set_reject_states(reject_states);
+        ruleset.set_blank(blank);
+        Ok(ruleset)
     }
 }
 
+/// parses the list of states of a header line, like ` 4, 5`
+fn parse_states(s: &str) -> Result<Vec<RuleState>, RulesetParseError> {
+    s.split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|state| !state.is_empty())
+        .map(|state| state.parse().map_err(|_| RulesetParseError::InvalidState { state: state.to_string() }))
+        .collect()
+}
+
 impl Display for Ruleset {
     /// display rules in the Markdown table format. in every cell format: {write}{move}{next_state}. first column contains char from alphabet, first row contains states (numbers).
     /// example:
@@ -138,16 +336,24 @@ impl Display for Ruleset {
     /// | _ | b>2 | _<3 | _>0 | _<1 |
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let mut table = String::new();
+        if self.blank != SPACE {
+            table.push_str(&format!("blank: {}\n", self.blank));
+        }
+        for (name, states) in [("accept", &self.accept_states), ("reject", &self.reject_states)] {
+            if !states.is_empty() {
+                table.push_str(&format!("{}: {}\n", name, states.iter().map(|s| s.to_string()).collect::<Vec<String>>().join(", ")));
+            }
+        }
         table.push_str("|   | ");
-        table.push_str(self.rules.keys().map(|s| s.to_string()).collect::<Vec<String>>().join(" | ").as_str());
+        table.push_str(self.states.iter().map(|s| s.to_string()).collect::<Vec<String>>().join(" | ").as_str());
         table.push_str("|\n|:-:|");
-        table.push_str(self.rules.keys().map(|_| ":-:|").collect::<Vec<&str>>().join("").as_str());
-        table.push_str("\n");
-        for symbol in self.alphabet.iter() {
-            table.push_str(&format!("| {} | ", symbol));
-            table.push_str(self.rules.keys().map(|state| {
-                if let Some(rule) = self.rules.get(state).and_then(|m| m.get(symbol)) {
-                    rule.to_string()
+        table.push_str(self.states.iter().map(|_| ":-:|").collect::<Vec<&str>>().join("").as_str());
+        table.push('\n');
+        for symbol in self.symbols.iter() {
+            table.push_str(&format!("| {} | ", symbol.iter().collect::<String>()));
+            table.push_str(self.states.iter().map(|state| {
+                if let Some(rules) = self.rules.get(state).and_then(|m| m.get(symbol)) {
+                    rules.iter().map(|r| r.to_string()).collect::<Vec<String>>().join(" ")
                 } else {
                     "     ".to_string()
                 }
@@ -158,10 +364,13 @@ impl Display for Ruleset {
     }
 }
 
+impl Error for RulesetParseError {}
+impl Error for RulesetError {}
+
 
 #[cfg(test)]
 mod test {
-    use crate::{Move, Rule, Ruleset, RulesetError};
+    use crate::{Move, Rule, RuleState, Ruleset, RulesetError};
     use crate::ruleset::RulesetParseError;
 
     #[test]
@@ -201,6 +410,17 @@ mod test {
         assert!(ruleset.parse::<Ruleset>().is_ok())
     }
     #[test]
+    fn test_ruleset_from_str_with_empty_cell() {
+        let ruleset =
+"|   | 0     | 1     |
+ |---|---    | ---   |
+ | a | a>1   |       |
+ | b | _<1   | a!0   |";
+        let ruleset = ruleset.parse::<Ruleset>().unwrap();
+        assert_eq!(ruleset.find(&1.into(), &'a').unwrap_err(), RulesetError::RuleNotFound {state: 1.into(), symbols: vec!['a']});
+        assert_eq!(ruleset.to_string().parse::<Ruleset>().unwrap(), ruleset);
+    }
+    #[test]
     fn test_ruleset_serde() {
         let ruleset =
 "|   | 0     | 1     | 2     | 3   |
@@ -224,29 +444,153 @@ mod test {
     fn test_ruleset_with_error() {
         let ruleset =
             "|   | 0     | 1     | 2     | 1   |";
-        assert_eq!(ruleset.parse::<Ruleset>().unwrap_err(), RulesetParseError::DuplicateState {state: 1});
+        assert_eq!(ruleset.parse::<Ruleset>().unwrap_err(), RulesetParseError::DuplicateState {state: 1.into()});
         let ruleset =
             "|   | 0     | 1     | 2     | 3   |
              | a | aa1   | a<2   | b>3   | a<0 | a<0 |";
         assert_eq!(ruleset.parse::<Ruleset>().unwrap_err(), RulesetParseError::InvalidRule {row: 0, col: 0, format: " aa1   ".to_string()});
     }
 
+    #[test]
+    fn test_multi_tape_ruleset() {
+        let ruleset =
+"|    | 0     | 1     |
+ |:-:|:-:    |:-:    |
+ | a_ | a>a>0 | a!_!1 |
+ | __ | _<_<1 | _!_!1 |";
+        let ruleset = ruleset.parse::<Ruleset>().unwrap();
+        assert_eq!(ruleset.tapes(), 2);
+        assert_eq!(ruleset.alphabet, vec!['a', '_']);
+        assert_eq!(ruleset.symbols, vec![vec!['a', '_'], vec!['_', '_']]);
+        assert_eq!(ruleset.find_symbols(&0.into(), &['a', '_']).unwrap(), Rule::with_tapes(vec![('a', Move::Right), ('a', Move::Right)], 0));
+        assert_eq!(ruleset.find_symbols(&1.into(), &['_', '_']).unwrap(), Rule::with_tapes(vec![('_', Move::Stop), ('_', Move::Stop)], 1));
+        assert_eq!(ruleset.find_symbols(&0.into(), &['a', 'a']).unwrap_err(), RulesetError::RuleNotFound {state: 0.into(), symbols: vec!['a', 'a']});
+        assert_eq!(ruleset.to_string().parse::<Ruleset>().unwrap(), ruleset);
+    }
+
+    #[test]
+    fn test_multi_tape_ruleset_with_error() {
+        let ruleset =
+            "|    | 0     |
+             | a_ | a>a>0 |
+             | _  | _<0   |";
+        assert_eq!(ruleset.parse::<Ruleset>().unwrap_err(), RulesetParseError::InvalidSymbol {row: 1});
+        let ruleset =
+            "|    | 0     |
+             | a_ | a>0 |";
+        assert_eq!(ruleset.parse::<Ruleset>().unwrap_err(), RulesetParseError::InvalidRule {row: 0, col: 0, format: " a>0 ".to_string()});
+    }
+
+    #[test]
+    fn test_ruleset_with_wildcards() {
+        let ruleset = "\
+|    | 0     | 1     |
+|:-: |:-:    |:-:    |
+| ab | a!a!0 | a!a!1 |
+| a* | *<*<0 | a!a!1 |
+| ** | *>*>0 | b!b!1 |
+| *b | b>b>1 | b!b!1 |".parse::<Ruleset>().unwrap();
+        assert_eq!(ruleset.alphabet(), &vec!['a', 'b']);
+        let state: RuleState = 0.into();
+        // the exact row goes first, then the rows with fewer wildcards, then the table order
+        assert_eq!(ruleset.matching_row(&state, &['a', 'b']), Some(&vec!['a', 'b']));
+        assert_eq!(ruleset.matching_row(&state, &['a', 'c']), Some(&vec!['a', '*']));
+        assert_eq!(ruleset.matching_row(&state, &['c', 'c']), Some(&vec!['*', '*']));
+        assert_eq!(ruleset.matching_row(&state, &['c', 'b']), Some(&vec!['*', 'b']));
+        assert_eq!(ruleset.find_symbols(&state, &['c', 'c']).unwrap().to_string(), "*>*>0");
+        assert_eq!(ruleset.cell(&state, &['c', 'c']), &[]);
+        assert_eq!(ruleset.to_string().parse::<Ruleset>().unwrap(), ruleset);
+    }
+
+    #[test]
+    fn test_nondeterministic_ruleset() {
+        let ruleset =
+"|   | 0       | 1   |
+ |:-:|:-:      |:-:  |
+ | a | a>0 b>1 | a!1 |
+ | b | b>0     | b!1 |";
+        let ruleset = ruleset.parse::<Ruleset>().unwrap();
+        assert!(!ruleset.is_deterministic());
+        assert_eq!(ruleset.find_all(&0.into(), &['a']), &[Rule::new('a', Move::Right, 0), Rule::new('b', Move::Right, 1)]);
+        assert_eq!(ruleset.find_all(&0.into(), &['c']), &[]);
+        assert_eq!(ruleset.find(&0.into(), &'a').unwrap_err(), RulesetError::AmbiguousRule {state: 0.into(), symbols: vec!['a']});
+        assert_eq!(ruleset.find(&0.into(), &'b').unwrap(), Rule::new('b', Move::Right, 0));
+        assert_eq!(ruleset.to_string().parse::<Ruleset>().unwrap(), ruleset);
+    }
+
+    #[test]
+    fn test_ruleset_with_halting_states() {
+        let ruleset =
+"
+accept: 2
+reject: 3, 4
+|   | 0   | 1   |
+|:-:|:-:  |:-:  |
+| a | a>1 | a>3 |
+| _ | _!4 | _!2 |";
+        let ruleset = ruleset.parse::<Ruleset>().unwrap();
+        assert_eq!(ruleset.accept_states(), &vec![2.into()]);
+        assert_eq!(ruleset.reject_states(), &vec![3.into(), 4.into()]);
+        assert!(ruleset.is_recognizer());
+        assert!(ruleset.is_halting_state(&3.into()));
+        assert!(!ruleset.is_halting_state(&1.into()));
+        assert_eq!(ruleset.to_string().parse::<Ruleset>().unwrap(), ruleset);
+        assert_eq!("accept 2\n|   | 0   |".parse::<Ruleset>().unwrap_err(), RulesetParseError::InvalidHeader { line: "accept 2".to_string() });
+        assert_eq!("accept: x?\n|   | 0   |".parse::<Ruleset>().unwrap_err(), RulesetParseError::InvalidState { state: "x?".to_string() });
+    }
+
+    #[test]
+    fn test_ruleset_with_named_states() {
+        let ruleset = "\
+accept: done
+|   | q_start | carry |
+|:-:|:-:      |:-:    |
+| 0 | 0>q_start | 1!done |
+| 1 | 1>q_start | 0<carry |
+| _ | _<carry | 1!done |".parse::<Ruleset>().unwrap();
+        let state = |name: &str| name.parse::<RuleState>().unwrap();
+        assert_eq!(ruleset.initial_state(), state("q_start"));
+        assert_eq!(ruleset.states(), &vec![state("q_start"), state("carry")]);
+        assert_eq!(ruleset.find(&state("carry"), &'1').unwrap(), Rule::new('0', Move::Left, state("carry")));
+        assert!(ruleset.is_halting_state(&state("done")));
+        assert_eq!(ruleset.to_string().parse::<Ruleset>().unwrap(), ruleset);
+
+        // numeric names are numbers, as in the tables written before the names
+        let ruleset = "|   | 00  | 01  |\n|:-:|:-:|:-:|\n| a | a>1 | a!00 |".parse::<Ruleset>().unwrap();
+        assert_eq!(ruleset.states(), &vec![0.into(), 1.into()]);
+        assert_eq!(ruleset.find(&1.into(), &'a').unwrap(), Rule::new('a', Move::Stop, 0));
+    }
+
+    #[test]
+    fn test_ruleset_with_blank() {
+        let ruleset = "\
+blank: λ
+|   | 0   |
+|:-:|:-:  |
+| _ | _>0 |
+| λ | λ!0 |".parse::<Ruleset>().unwrap();
+        assert_eq!(ruleset.blank(), 'λ');
+        assert!(ruleset.to_string().starts_with("blank: λ\n"));
+        assert_eq!(ruleset.to_string().parse::<Ruleset>().unwrap(), ruleset);
+        assert_eq!("|   | 0   |\n| a | a!0 |".parse::<Ruleset>().unwrap().blank(), '_');
+        assert_eq!("blank: ab\n|   | 0   |".parse::<Ruleset>().unwrap_err(), RulesetParseError::InvalidHeader { line: "blank: ab".to_string() });
+    }
+
     fn check_ruleset(ruleset: Ruleset) {
         assert_eq!(ruleset.rules.len(), 4);
+        assert!(ruleset.is_deterministic());
+        assert!(!ruleset.is_recognizer());
         assert_eq!(ruleset.alphabet, vec!['a', 'b']);
-        assert_eq!(ruleset.find(&0, &'a').unwrap(), Rule::new('a', Move::Right, 1));
-        assert_eq!(ruleset.find(&0, &'a').unwrap(), Rule::new('a', Move::Right, 1));
-        assert_eq!(ruleset.find(&0, &'b').unwrap(), Rule::new('_', Move::Left, 1));
-        assert_eq!(ruleset.find(&1, &'a').unwrap(), Rule::new('a', Move::Left, 2));
-        assert_eq!(ruleset.find(&1, &'b').unwrap(), Rule::new('a', Move::Right, 2));
-        assert_eq!(ruleset.find(&2, &'a').unwrap(), Rule::new('b', Move::Right, 3));
-        assert_eq!(ruleset.find(&2, &'b').unwrap(), Rule::new('a', Move::Left, 3));
-        assert_eq!(ruleset.find(&3, &'a').unwrap(), Rule::new('a', Move::Left, 0));
-        assert_eq!(ruleset.find(&3, &'b').unwrap(), Rule::new('a', Move::Stop, 0));
-        assert_eq!(ruleset.find(&4, &'b').unwrap_err(), RulesetError::RuleNotFound {state: 4, symbol: 'b'});
-        assert_eq!(ruleset.find(&1, &'c').unwrap_err(), RulesetError::RuleNotFound {state: 1, symbol: 'c'});
+        assert_eq!(ruleset.find(&0.into(), &'a').unwrap(), Rule::new('a', Move::Right, 1));
+        assert_eq!(ruleset.find(&0.into(), &'a').unwrap(), Rule::new('a', Move::Right, 1));
+        assert_eq!(ruleset.find(&0.into(), &'b').unwrap(), Rule::new('_', Move::Left, 1));
+        assert_eq!(ruleset.find(&1.into(), &'a').unwrap(), Rule::new('a', Move::Left, 2));
+        assert_eq!(ruleset.find(&1.into(), &'b').unwrap(), Rule::new('a', Move::Right, 2));
+        assert_eq!(ruleset.find(&2.into(), &'a').unwrap(), Rule::new('b', Move::Right, 3));
+        assert_eq!(ruleset.find(&2.into(), &'b').unwrap(), Rule::new('a', Move::Left, 3));
+        assert_eq!(ruleset.find(&3.into(), &'a').unwrap(), Rule::new('a', Move::Left, 0));
+        assert_eq!(ruleset.find(&3.into(), &'b').unwrap(), Rule::new('a', Move::Stop, 0));
+        assert_eq!(ruleset.find(&4.into(), &'b').unwrap_err(), RulesetError::RuleNotFound {state: 4.into(), symbols: vec!['b']});
+        assert_eq!(ruleset.find(&1.into(), &'c').unwrap_err(), RulesetError::RuleNotFound {state: 1.into(), symbols: vec!['c']});
     }
-}
-
-impl Error for RulesetParseError {}
-impl Error for RulesetError {}
\ No newline at end of file
+}
\ No newline at end of file
@@ -0,0 +1,236 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use crate::rule::{Rule, RuleState};
+use crate::ruleset::Ruleset;
+use crate::tape::Tape;
+
+/// a state is written as its name, and the name is checked when it is read
+impl Serialize for RuleState {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for RuleState {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<RuleState, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        name.parse().map_err(|_| de::Error::custom(format!("invalid state name: {}", name)))
+    }
+}
+
+/// serialized form of a tape: the runs of cells as strings, like the tape of a snapshot,
+/// so a tape whose head went far stays small
+#[derive(Serialize, Deserialize)]
+pub(crate) struct TapeData {
+    head: isize,
+    runs: Vec<(isize, String)>,
+    blank: char,
+}
+
+impl From<Tape> for TapeData {
+    fn from(tape: Tape) -> TapeData {
+        let runs = tape.runs().into_iter().map(|(start, cells)| (start, cells.into_iter().collect())).collect();
+        TapeData { head: tape.head(), runs, blank: tape.blank() }
+    }
+}
+
+impl From<TapeData> for Tape {
+    fn from(tape: TapeData) -> Tape {
+        let runs = tape.runs.into_iter().map(|(start, cells)| (start, cells.chars().collect())).collect();
+        Tape::from_runs(runs, tape.head, tape.blank)
+    }
+}
+
+/// serialized form of a ruleset: the table with the cells listed column by column,
+/// because the rows are tuples of symbols and can't be keys of a JSON object
+#[derive(Serialize, Deserialize)]
+pub(crate) struct RulesetTable {
+    states: Vec<RuleState>,
+    symbols: Vec<Vec<char>>,
+    cells: Vec<Cell>,
+    accept_states: Vec<RuleState>,
+    reject_states: Vec<RuleState>,
+    blank: char,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Cell {
+    state: RuleState,
+    symbols: Vec<char>,
+    rules: Vec<Rule>,
+}
+
+impl From<Ruleset> for RulesetTable {
+    fn from(ruleset: Ruleset) -> RulesetTable {
+        let mut cells = Vec::new();
+        for state in ruleset.states() {
+            for row in ruleset.symbols() {
+                let rules = ruleset.cell(state, row);
+                if !rules.is_empty() {
+                    cells.push(Cell { state: state.clone(), symbols: row.clone(), rules: rules.to_vec() });
+                }
+            }
+        }
+        RulesetTable {
+            states: ruleset.states().clone(),
+            symbols: ruleset.symbols().clone(),
+            cells,
+            accept_states: ruleset.accept_states().clone(),
+            reject_states: ruleset.reject_states().clone(),
+            blank: ruleset.blank(),
+        }
+    }
+}
+
+/// the table is checked when it is read: every cell must be in a column and a row of the table,
+/// and every row and every rule must be as wide as the first row
+impl TryFrom<RulesetTable> for Ruleset {
+    type Error = String;
+
+    fn try_from(table: RulesetTable) -> Result<Ruleset, String> {
+        let tapes = table.symbols.first().map_or(0, Vec::len);
+        if let Some(row) = table.symbols.iter().find(|row| row.len() != tapes) {
+            return Err(format!("row {:?} has {} symbols, expected {}", row, row.len(), tapes));
+        }
+        for cell in &table.cells {
+            if !table.states.contains(&cell.state) {
+                return Err(format!("cell of unknown state {}", cell.state));
+            }
+            if !table.symbols.contains(&cell.symbols) {
+                return Err(format!("cell of unknown row {:?}", cell.symbols));
+            }
+            if let Some(rule) = cell.rules.iter().find(|rule| rule.writes().len() != tapes || rule.moves().len() != tapes) {
+                return Err(format!("rule {} of state {} is not for {} tapes", rule, cell.state, tapes));
+            }
+        }
+        let mut rules: HashMap<RuleState, HashMap<Vec<char>, Vec<Rule>>> = table.states.iter()
+            .map(|state| (state.clone(), HashMap::new()))
+            .collect();
+        for cell in table.cells {
+            rules.entry(cell.state).or_default().insert(cell.symbols, cell.rules);
+        }
+        let mut ruleset = Ruleset::new(rules, table.symbols, table.states);
+        ruleset.set_accept_states(table.accept_states);
+        ruleset.set_reject_states(table.reject_states);
+        ruleset.set_blank(table.blank);
+        Ok(ruleset)
+    }
+}
+
+/// maps with keys which can't be keys of a JSON object, like tuples, written as lists of pairs
+pub(crate) mod pairs {
+    use super::*;
+
+    pub fn serialize<K: Serialize, V: Serialize, S: Serializer>(map: &HashMap<K, V>, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(map)
+    }
+
+    pub fn deserialize<'de, K, V, D>(deserializer: D) -> Result<HashMap<K, V>, D::Error>
+    where K: Deserialize<'de> + Eq + Hash, V: Deserialize<'de>, D: Deserializer<'de> {
+        Ok(Vec::<(K, V)>::deserialize(deserializer)?.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde::de::DeserializeOwned;
+    use serde::Serialize;
+    use crate::history::History;
+    use crate::rule::{Move, Rule, RuleState};
+    use crate::ruleset::{Ruleset, RulesetParseError};
+    use crate::tape::Tape;
+    use crate::turing::Turing;
+
+    const RULES: &str = "accept: 2\nreject: 3\nblank: .\n\
+|   | 0 | 1 | 2 | 3 |
+|:-:|:-:|:-:|:-:|:-:|
+| a | a>0 | b<1 | | |
+| . | .<1 | a!2 b!3 | | |
+| * | *>0 | | | |";
+
+    /// writes the value to JSON and reads it back
+    fn round_trip<T: Serialize + DeserializeOwned>(value: &T) -> T {
+        let json = serde_json::to_string(value).unwrap();
+        serde_json::from_str(&json).unwrap()
+    }
+
+    #[test]
+    fn test_ruleset_round_trip() {
+        let ruleset: Ruleset = RULES.parse().unwrap();
+        let read = round_trip(&ruleset);
+        assert_eq!(read, ruleset);
+        assert_eq!(read.to_string(), ruleset.to_string());
+        assert_eq!(read.alphabet(), ruleset.alphabet());
+    }
+
+    #[test]
+    fn test_tape_round_trip() {
+        let mut tape = Tape::with_blank("ab.ba".chars().collect(), 1, -2, '.');
+        tape.set_head(-70);
+        let read = round_trip(&tape);
+        assert_eq!(read.to_string(), tape.to_string());
+        assert_eq!(read.head(), -70);
+        assert_eq!(read.data(), tape.data());
+        assert_eq!(read.blank(), '.');
+    }
+
+    #[test]
+    fn test_far_tape_round_trip() {
+        let mut tape: Tape = "ab".parse().unwrap();
+        tape.set_head(1_000_000_000);
+        tape.apply_rule(&Rule::new('c', Move::Left, 0));
+        let json = serde_json::to_string(&tape).unwrap();
+        assert_eq!(json, r#"{"head":999999999,"runs":[[0,"ab"],[999999999,"_c"]],"blank":"_"}"#);
+        let read: Tape = serde_json::from_str(&json).unwrap();
+        assert_eq!(format!("{:#}", read), format!("{:#}", tape));
+        assert_eq!(read.runs(), tape.runs());
+    }
+
+    #[test]
+    fn test_ruleset_is_checked() {
+        let table = |cells: &str, symbols: &str| format!(r#"{{"states":["0","1"],"symbols":{},"cells":{},"accept_states":[],"reject_states":[],"blank":"_"}}"#, symbols, cells);
+        let cell = |state: &str, symbols: &str, writes: &str| format!(r#"[{{"state":"{}","symbols":{},"rules":[{{"writes":{},"moves":["Right"],"next_state":"1"}}]}}]"#, state, symbols, writes);
+        let read = |json: &str| serde_json::from_str::<Ruleset>(json).map_err(|e| e.to_string());
+        assert!(read(&table(&cell("0", r#"["a"]"#, r#"["b"]"#), r#"[["a"]]"#)).is_ok());
+        assert!(read(&table(&cell("2", r#"["a"]"#, r#"["b"]"#), r#"[["a"]]"#)).unwrap_err().starts_with("cell of unknown state 2"));
+        assert!(read(&table(&cell("0", r#"["b"]"#, r#"["b"]"#), r#"[["a"]]"#)).unwrap_err().starts_with("cell of unknown row ['b']"));
+        assert!(read(&table(&cell("0", r#"["a"]"#, r#"["b"]"#), r#"[["a"],["b","c"]]"#)).unwrap_err().starts_with("row ['b', 'c'] has 2 symbols, expected 1"));
+        assert!(read(&table(&cell("0", r#"["a"]"#, r#"["b","c"]"#), r#"[["a"]]"#)).unwrap_err().starts_with("rule "));
+    }
+
+    #[test]
+    fn test_state_is_checked() {
+        let state: RuleState = "q_1".parse().unwrap();
+        assert_eq!(round_trip(&state), state);
+        assert_eq!(serde_json::to_string(&state).unwrap(), "\"q_1\"");
+        assert!(serde_json::from_str::<RuleState>("\"a|b\"").is_err());
+        assert!(serde_json::from_str::<Rule>(r#"{"writes":["a"],"moves":["Right"],"next_state":""}"#).is_err());
+    }
+
+    #[test]
+    fn test_run_round_trip() {
+        let ruleset: Ruleset = "|   | 0 | 1 |\n|:-:|:-:|:-:|\n| a | a>0 | b!1 |\n| _ | _<1 | |".parse().unwrap();
+        let tape: Tape = "aa".parse().unwrap();
+        let mut turing = Turing::new(tape, 0, ruleset);
+        turing.collect_statistics(true);
+        let mut history = History::with_interval(turing.tapes().to_vec(), 2);
+        while !turing.is_halted() {
+            let transition = turing.step().unwrap();
+            history.push(transition, turing.tapes());
+        }
+        let read = round_trip(&history);
+        assert_eq!(read.len(), history.len());
+        assert_eq!(read.transitions()[2].deltas(), history.transitions()[2].deltas());
+        assert_eq!(read.transitions()[2].rule(), history.transitions()[2].rule());
+        assert_eq!(read.tapes(3).unwrap()[0].to_string(), history.tapes(3).unwrap()[0].to_string());
+        let statistics = turing.statistics().unwrap();
+        assert_eq!(&round_trip(statistics), statistics);
+        let snapshot = turing.snapshot(true);
+        assert_eq!(round_trip(&snapshot).to_string(), snapshot.to_string());
+        let run = turing.run(10).unwrap();
+        assert_eq!(round_trip(&run), run);
+        let error = RulesetParseError::InvalidRule { row: 1, col: 2, format: "a?".to_string() };
+        assert_eq!(round_trip(&error), error);
+    }
+}
@@ -0,0 +1,305 @@
+use std::error::Error;
+use std::fmt;
+use std::fmt::Display;
+use std::str::FromStr;
+use crate::rule::RuleState;
+use crate::ruleset::{Ruleset, RulesetParseError};
+use crate::tape::{Tape, SPACE};
+use crate::turing::Turing;
+
+/// The configuration of a paused machine: the state, the tapes and the number of made steps.
+/// The ruleset is identified by its hash and may be embedded into the snapshot.
+/// Breakpoints, observers, the undo log and the loop detection are not saved.
+///
+/// The text format is a list of `key: value` lines, one `tape:` line per tape with the head offset,
+/// the index and the cells of the tape, and optionally the ruleset in the Markdown format after a `ruleset:` line.
+/// A `blank:` line sets the blank of the tapes after it, `_` by default. The cells after a long stretch of blanks
+/// go on a `run:` line with the position of their first cell, see `Tape::runs`:
+/// ```text
+/// state: 3
+/// steps: 120
+/// halted: false
+/// tape: -2 5 ab_ba
+/// run: 1000000 c
+/// hash: 5c1b6a0e2d9f3e47
+/// ruleset:
+/// |   | 0 | 1 |
+/// ...
+/// ```
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Snapshot {
+    state: RuleState,
+    steps: usize,
+    halted: bool,
+    tapes: Vec<Tape>,
+    hash: u64,
+    ruleset: Option<Ruleset>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SnapshotError {
+    /// the snapshot was taken with another ruleset
+    RulesetMismatch { expected: u64, found: u64 },
+    /// the ruleset is neither embedded into the snapshot nor given
+    MissingRuleset,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SnapshotParseError {
+    InvalidLine { line: String },
+    MissingField { field: String },
+    InvalidRuleset { error: RulesetParseError },
+}
+
+impl Turing {
+    /// saves the configuration of the machine. The ruleset is embedded when `with_ruleset` is set,
+    /// otherwise the same ruleset must be given to `Snapshot::resume`.
+    pub fn snapshot(&self, with_ruleset: bool) -> Snapshot {
+        Snapshot {
+            state: self.state(),
+            steps: self.steps(),
+            halted: self.is_halted(),
+            tapes: self.tapes().clone(),
+            hash: ruleset_hash(self.ruleset()),
+            ruleset: with_ruleset.then(|| self.ruleset().clone()),
+        }
+    }
+}
+
+impl Snapshot {
+    /// restores the machine. `ruleset` takes precedence over the embedded one, and must be the one the snapshot was taken with.
+    pub fn resume(self, ruleset: Option<Ruleset>) -> Result<Turing, SnapshotError> {
+        let ruleset = ruleset.or(self.ruleset).ok_or(SnapshotError::MissingRuleset)?;
+        let found = ruleset_hash(&ruleset);
+        if found != self.hash {
+            return Err(SnapshotError::RulesetMismatch { expected: self.hash, found });
+        }
+        let mut turing = Turing::with_tapes(self.tapes, self.state, ruleset);
+        turing.restore_progress(self.steps, self.halted);
+        Ok(turing)
+    }
+
+    pub fn state(&self) -> RuleState {
+        self.state.clone()
+    }
+
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    pub fn tapes(&self) -> &Vec<Tape> {
+        &self.tapes
+    }
+
+    /// hash of the ruleset the snapshot was taken with
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+
+    pub fn ruleset(&self) -> Option<&Ruleset> {
+        self.ruleset.as_ref()
+    }
+}
+
+/// FNV-1a hash of the Markdown form of the ruleset. Unlike `DefaultHasher` it is the same on every machine and Rust version.
+fn ruleset_hash(ruleset: &Ruleset) -> u64 {
+    ruleset.to_string().bytes().fold(0xcbf29ce484222325, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x100000001b3))
+}
+
+impl Display for Snapshot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "state: {}", self.state)?;
+        writeln!(f, "steps: {}", self.steps)?;
+        writeln!(f, "halted: {}", self.halted)?;
+        let mut blank = SPACE;
+        for tape in &self.tapes {
+            if tape.blank() != blank {
+                blank = tape.blank();
+                writeln!(f, "blank: {}", blank)?;
+            }
+            let mut runs = tape.runs().into_iter();
+            // the first run starts at the head offset
+            let cells = runs.next().map(|(_, cells)| cells).unwrap_or_default();
+            writeln!(f, "tape: {} {} {}", tape.head_offset(), tape.index(), cells.iter().collect::<String>())?;
+            for (start, cells) in runs {
+                writeln!(f, "run: {} {}", start, cells.iter().collect::<String>())?;
+            }
+        }
+        writeln!(f, "hash: {:016x}", self.hash)?;
+        if let Some(ruleset) = &self.ruleset {
+            writeln!(f, "ruleset:")?;
+            write!(f, "{}", ruleset)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Snapshot {
+    type Err = SnapshotParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut state = None;
+        let mut steps = None;
+        let mut halted = None;
+        let mut tapes = Vec::new();
+        let mut hash = None;
+        let mut ruleset = None;
+        let mut blank = SPACE;
+        let mut lines = s.lines();
+        while let Some(line) = lines.next() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let invalid_line = || SnapshotParseError::InvalidLine { line: line.to_string() };
+            let (key, value) = line.split_once(':').ok_or_else(invalid_line)?;
+            match key.trim() {
+                "state" => state = Some(value.trim().parse().map_err(|_| invalid_line())?),
+                "steps" => steps = Some(value.trim().parse().map_err(|_| invalid_line())?),
+                "halted" => halted = Some(value.trim().parse().map_err(|_| invalid_line())?),
+                "blank" => {
+                    let mut chars = value.trim().chars();
+                    blank = match (chars.next(), chars.next()) {
+                        (Some(c), None) => c,
+                        _ => return Err(invalid_line()),
+                    }
+                }
+                "hash" => hash = Some(u64::from_str_radix(value.trim(), 16).map_err(|_| invalid_line())?),
+                "tape" => {
+                    // the cells are the rest of the line, they may contain spaces
+                    let mut parts = value.strip_prefix(' ').unwrap_or(value).splitn(3, ' ');
+                    let head_offset: isize = parts.next().and_then(|p| p.parse().ok()).ok_or_else(invalid_line)?;
+                    let index: isize = parts.next().and_then(|p| p.parse().ok()).ok_or_else(invalid_line)?;
+                    let data = parts.next().unwrap_or("").chars().collect();
+                    tapes.push((head_offset + index, vec![(head_offset, data)], blank));
+                }
+                "run" => {
+                    let mut parts = value.strip_prefix(' ').unwrap_or(value).splitn(2, ' ');
+                    let start = parts.next().and_then(|p| p.parse().ok()).ok_or_else(invalid_line)?;
+                    let data = parts.next().unwrap_or("").chars().collect();
+                    tapes.last_mut().ok_or_else(invalid_line)?.1.push((start, data));
+                }
+                // the ruleset takes the rest of the snapshot
+                "ruleset" => {
+                    let table = lines.by_ref().collect::<Vec<&str>>().join("\n");
+                    ruleset = Some(Ruleset::from_str(&table).map_err(|error| SnapshotParseError::InvalidRuleset { error })?);
+                }
+                _ => return Err(invalid_line()),
+            }
+        }
+        let missing = |field: &str| SnapshotParseError::MissingField { field: field.to_string() };
+        if tapes.is_empty() {
+            return Err(missing("tape"));
+        }
+        Ok(Snapshot {
+            state: state.ok_or_else(|| missing("state"))?,
+            steps: steps.ok_or_else(|| missing("steps"))?,
+            halted: halted.ok_or_else(|| missing("halted"))?,
+            tapes: tapes.into_iter().map(|(head, runs, blank)| Tape::from_runs(runs, head, blank)).collect(),
+            hash: hash.ok_or_else(|| missing("hash"))?,
+            ruleset,
+        })
+    }
+}
+
+impl Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SnapshotError::RulesetMismatch { expected, found } => write!(f, "Snapshot was taken with another ruleset: expected hash {:016x}, found {:016x}", expected, found),
+            SnapshotError::MissingRuleset => write!(f, "Snapshot doesnt contain the ruleset"),
+        }
+    }
+}
+
+impl Display for SnapshotParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SnapshotParseError::InvalidLine { line } => write!(f, "Invalid snapshot line: {}", line),
+            SnapshotParseError::MissingField { field } => write!(f, "Missing snapshot field: {}", field),
+            SnapshotParseError::InvalidRuleset { error } => write!(f, "Invalid ruleset in snapshot: {}", error),
+        }
+    }
+}
+
+impl Error for SnapshotError {}
+impl Error for SnapshotParseError {}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+    use crate::ruleset::Ruleset;
+    use crate::snapshot::{Snapshot, SnapshotError, SnapshotParseError};
+    use crate::tape::Tape;
+    use crate::turing::Turing;
+
+    // moves right over "a" and replaces the first "b" with "a"
+    const RULES: &str = "\
+|   | 0   |
+|:-:|:-:  |
+| a | a>0 |
+| b | a!0 |";
+
+    #[test]
+    fn test_snapshot_resume() {
+        let mut turing = Turing::new(Tape::new("aab".chars().collect(), 0, 0), 0, Ruleset::from_str(RULES).unwrap());
+        turing.step().unwrap();
+        let text = turing.snapshot(true).to_string();
+        let snapshot = Snapshot::from_str(&text).unwrap();
+        assert_eq!(snapshot.steps(), 1);
+        assert_eq!(snapshot.to_string(), text);
+
+        let mut resumed = snapshot.resume(None).unwrap();
+        assert_eq!(resumed.steps(), 1);
+        assert_eq!(resumed.tape().head(), 1);
+        resumed.run(10).unwrap();
+        turing.run(10).unwrap();
+        assert_eq!(resumed.steps(), turing.steps());
+        assert_eq!(resumed.tape().to_string(), turing.tape().to_string());
+        assert!(resumed.is_halted());
+    }
+
+    #[test]
+    fn test_snapshot_ruleset() {
+        let turing = Turing::new(Tape::new("ab".chars().collect(), 0, 0), 0, Ruleset::from_str(RULES).unwrap());
+        let snapshot = Snapshot::from_str(&turing.snapshot(false).to_string()).unwrap();
+        assert!(snapshot.ruleset().is_none());
+        assert_eq!(snapshot.clone().resume(None).err(), Some(SnapshotError::MissingRuleset));
+        let other = Ruleset::from_str(&RULES.replace("a!0", "b!0")).unwrap();
+        assert!(matches!(snapshot.clone().resume(Some(other)), Err(SnapshotError::RulesetMismatch { .. })));
+        assert!(snapshot.resume(Some(Ruleset::from_str(RULES).unwrap())).is_ok());
+    }
+
+    #[test]
+    fn test_snapshot_blank() {
+        let mut rules = Ruleset::from_str(RULES).unwrap();
+        rules.set_blank('λ');
+        let tapes = vec![Tape::new("ab".chars().collect(), 0, 0), Tape::with_blank("ab".chars().collect(), 3, 0, 'λ')];
+        let text = Turing::with_tapes(tapes, 0, rules).snapshot(true).to_string();
+        assert!(text.contains("tape: 0 0 ab\nblank: λ\ntape: 0 3 abλλ\n"));
+        let snapshot = Snapshot::from_str(&text).unwrap();
+        assert_eq!(snapshot.tapes().iter().map(|t| t.blank()).collect::<Vec<char>>(), vec!['_', 'λ']);
+        assert_eq!(snapshot.ruleset().unwrap().blank(), 'λ');
+    }
+
+    #[test]
+    fn test_snapshot_far_jump() {
+        let mut tape = Tape::new("ab".chars().collect(), 0, 0);
+        tape.set_head(1_000_000_000);
+        let text = Turing::new(tape, 0, Ruleset::from_str(RULES).unwrap()).snapshot(false).to_string();
+        assert!(text.contains("tape: 0 1000000000 ab\nrun: 1000000000 _\n"));
+        let snapshot = Snapshot::from_str(&text).unwrap();
+        assert_eq!(snapshot.tapes()[0].head(), 1_000_000_000);
+        assert_eq!(format!("{:#}", snapshot.tapes()[0]), " a  b  _×999999998 [_]");
+        assert_eq!(snapshot.to_string(), text);
+    }
+
+    #[test]
+    fn test_snapshot_from_str_errors() {
+        assert_eq!(Snapshot::from_str("state: 0\nsteps: 0\nhalted: false\nhash: 0").err(), Some(SnapshotParseError::MissingField { field: "tape".to_string() }));
+        assert_eq!(Snapshot::from_str("state: x?").err(), Some(SnapshotParseError::InvalidLine { line: "state: x?".to_string() }));
+        assert_eq!(Snapshot::from_str("tape: 0 0 a\nsteps: 0\nhalted: false\nhash: 0").err(), Some(SnapshotParseError::MissingField { field: "state".to_string() }));
+        assert_eq!(Snapshot::from_str("run: 5 a").err(), Some(SnapshotParseError::InvalidLine { line: "run: 5 a".to_string() }));
+    }
+}
@@ -0,0 +1,135 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fmt::Display;
+use crate::rule::RuleState;
+use crate::tape::Tape;
+
+/// Profile of a run: how many times every rule fired, how many times every state was entered,
+/// and which cells the heads visited. Collected by `Turing` after `collect_statistics(true)`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Statistics {
+    steps: usize,
+    // fired rules by the state and the symbols under the heads
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::pairs"))]
+    rules: HashMap<(RuleState, Vec<char>), usize>,
+    states: HashMap<RuleState, usize>,
+    // visited cells and the (leftmost, rightmost) head position of every tape
+    cells: Vec<HashSet<isize>>,
+    head_ranges: Vec<(isize, isize)>,
+}
+
+impl Statistics {
+    /// starts the profile in the given configuration, which counts as a visit of the state
+    pub(crate) fn new(state: RuleState, tapes: &[Tape]) -> Statistics {
+        Statistics {
+            steps: 0,
+            rules: HashMap::new(),
+            states: HashMap::from([(state, 1)]),
+            cells: tapes.iter().map(|t| HashSet::from([t.head()])).collect(),
+            head_ranges: tapes.iter().map(|t| (t.head(), t.head())).collect(),
+        }
+    }
+
+    /// records a step made in `state` on `symbols`, which led to `next_state` and moved the heads over `tapes`
+    pub(crate) fn record(&mut self, state: &RuleState, symbols: Vec<char>, next_state: &RuleState, tapes: &[Tape]) {
+        self.steps += 1;
+        *self.rules.entry((state.clone(), symbols)).or_default() += 1;
+        *self.states.entry(next_state.clone()).or_default() += 1;
+        for ((cells, (leftmost, rightmost)), tape) in self.cells.iter_mut().zip(self.head_ranges.iter_mut()).zip(tapes) {
+            cells.insert(tape.head());
+            *leftmost = (*leftmost).min(tape.head());
+            *rightmost = (*rightmost).max(tape.head());
+        }
+    }
+
+    /// number of recorded steps
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    /// how many times the rule for the state and the symbols fired
+    pub fn rule_count(&self, state: impl Into<RuleState>, symbols: &[char]) -> usize {
+        self.rules.get(&(state.into(), symbols.to_vec())).copied().unwrap_or(0)
+    }
+
+    pub fn rules(&self) -> &HashMap<(RuleState, Vec<char>), usize> {
+        &self.rules
+    }
+
+    /// how many times the machine was in the state, the initial state included
+    pub fn visits(&self, state: impl Into<RuleState>) -> usize {
+        self.states.get(&state.into()).copied().unwrap_or(0)
+    }
+
+    pub fn states(&self) -> &HashMap<RuleState, usize> {
+        &self.states
+    }
+
+    /// number of distinct cells visited by the head of every tape
+    pub fn cells_touched(&self) -> Vec<usize> {
+        self.cells.iter().map(HashSet::len).collect()
+    }
+
+    /// the leftmost and the rightmost head position of every tape
+    pub fn head_ranges(&self) -> &Vec<(isize, isize)> {
+        &self.head_ranges
+    }
+}
+
+impl Display for Statistics {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Steps:\t{}", self.steps)?;
+        writeln!(f, "Rules fired:")?;
+        let mut rules: Vec<_> = self.rules.iter().collect();
+        rules.sort();
+        for ((state, symbols), count) in rules {
+            writeln!(f, "\t{}\t{}\t{}", state, symbols.iter().collect::<String>(), count)?;
+        }
+        writeln!(f, "State visits:")?;
+        let mut states: Vec<_> = self.states.iter().collect();
+        states.sort();
+        for (state, count) in states {
+            writeln!(f, "\t{}\t{}", state, count)?;
+        }
+        for (i, (cells, (leftmost, rightmost))) in self.cells_touched().iter().zip(self.head_ranges.iter()).enumerate() {
+            let name = if self.cells.len() == 1 { "Tape".to_string() } else { format!("Tape {}", i + 1) };
+            writeln!(f, "{}:\t{} cells touched, head from {} to {}", name, cells, leftmost, rightmost)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+    use crate::ruleset::Ruleset;
+    use crate::tape::Tape;
+    use crate::turing::Turing;
+
+    #[test]
+    fn test_statistics() {
+        // goes to the end of the word and back to its start
+        let rules = Ruleset::from_str("\
+|   | 0   | 1   |
+|:-:|:-:  |:-:  |
+| a | a>0 | a<1 |
+| _ | _<1 | _!1 |").unwrap();
+        let mut turing = Turing::new(Tape::new("aa".chars().collect(), 0, 0), 0, rules);
+        turing.step().unwrap();
+        assert!(turing.statistics().is_none());
+        turing.collect_statistics(true);
+        turing.run(100).unwrap();
+
+        let statistics = turing.statistics().unwrap();
+        assert_eq!(statistics.steps(), turing.steps() - 1);
+        assert_eq!(statistics.rule_count(0, &['a']), 1);
+        assert_eq!(statistics.rule_count(0, &['_']), 1);
+        assert_eq!(statistics.rule_count(1, &['a']), 2);
+        assert_eq!(statistics.rule_count(1, &['_']), 1);
+        assert_eq!(statistics.visits(0), 2);
+        assert_eq!(statistics.visits(1), 4);
+        assert_eq!(statistics.cells_touched(), vec![4]);
+        assert_eq!(statistics.head_ranges(), &vec![(-1, 2)]);
+    }
+}
@@ -1,108 +1,257 @@
-use std::cmp::{max, Ordering};
+use std::cmp::{max, min};
+use std::collections::BTreeMap;
 use std::fmt;
+use std::hash::{Hash, Hasher};
 use std::str::FromStr;
-use crate::rule::{Move, Rule};
+use crate::rule::Move;
+#[cfg(test)]
+use crate::rule::Rule;
 
 
-const SPACE: char = '_';
+/// the blank of tapes and rulesets which don't declare another one
+pub(crate) const SPACE: char = '_';
+const CHUNK_SIZE: isize = 64;
 
+/// A tape infinite in both directions. Only the chunks of the tape with non-blank cells are stored,
+/// so the head may jump far away or walk for a long time without filling the memory with blanks.
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "crate::serialization::TapeData", into = "crate::serialization::TapeData"))]
 pub struct Tape {
-    data: Vec<char>,
-    index: isize,
-    head_offset: isize,
+    blank: char,
+    // chunks of CHUNK_SIZE cells by the position of their first cell divided by CHUNK_SIZE
+    chunks: BTreeMap<isize, Box<[char; CHUNK_SIZE as usize]>>,
+    head: isize,
+    // the leftmost and the rightmost cells of the data: the initial data and every cell visited by the head
+    left: isize,
+    right: isize,
 }
 
 impl Tape {
+    /// creates a tape with `data` starting at the position `data_start_at` and the head at `head`
     pub fn new(data: Vec<char>, head: isize, data_start_at: isize) -> Tape {
-        // head0 is the head position relative to the data_start_at.
-        let head0 = head - data_start_at;
-        let data_len = data.len();
-        // head_offset is the head position relative to the data. It is used to calculate the index of the data.
-        // head_offset = min(head, data_start_at)
-        match head0.cmp(&0) {
-            // padding right with spaces when head is equal to data_start_at and data is empty.
-            Ordering::Equal => Tape { data: if data.is_empty() { vec![SPACE] } else { data }, index: 0, head_offset: head },
-            // padding left with spaces when head is less than data_start_at.
-            // ex: head is 0, data_start_at is 3, head0 is -3. Then we need to add 3 spaces to the left of the data.
-            // So, head is still 0 and points to the data at index 0 (head - head_offset(which inited as head) = 3 - 3).
-            Ordering::Less => Tape { data: [vec![SPACE; -head0 as usize], data].concat(), index: 0, head_offset: head },
-            // padding right with spaces when head is greater than data_start_at + len(data).
-            // ex: head is 6, data_start_at is 3, len(data) is 1, head0 is 3.
-            // Then we need to add max(0, head0 + 1 - len(data)) = max(0, 3+1-1) = 3 spaces to the right of the data. new len(data) is 4
-            // So, head is still 6 and points to the data at index 3 (head - head_offset(which is data_start_at) = 6 - 3 = 3).
-            Ordering::Greater => Tape { data: [data, vec![SPACE; max(0, head0 + 1 - data_len as isize) as usize]].concat(), index: head0, head_offset: data_start_at },
+        Tape::with_blank(data, head, data_start_at, SPACE)
+    }
+
+    /// creates a tape filled with `blank` outside of `data`
+    pub fn with_blank(data: Vec<char>, head: isize, data_start_at: isize, blank: char) -> Tape {
+        let mut tape = Tape {
+            blank,
+            chunks: BTreeMap::new(),
+            head,
+            left: min(head, data_start_at),
+            right: max(data_start_at + data.len() as isize - 1, head),
+        };
+        for (position, c) in (data_start_at..).zip(data) {
+            tape.write_at(position, c);
         }
+        tape
     }
 
     pub fn read(&self) -> char {
-        self.data.get(self.index as usize).unwrap_or(&SPACE).clone()
+        self.get(self.head)
+    }
+
+    pub fn blank(&self) -> char {
+        self.blank
+    }
+
+    /// symbol at the position, blank outside of the data
+    pub fn get(&self, position: isize) -> char {
+        self.chunks.get(&position.div_euclid(CHUNK_SIZE))
+            .map_or(self.blank, |chunk| chunk[position.rem_euclid(CHUNK_SIZE) as usize])
     }
 
+    #[cfg(test)]
     pub(crate) fn apply_rule(&mut self, rule: &Rule) {
-        self.write(rule.write);
-        self.move_head(&rule.mov);
-        self.extend();
+        self.apply(rule.write(), &rule.mov());
+    }
+
+    /// writes the symbol under the head and moves the head. Used for one tape of a multi-tape rule.
+    pub(crate) fn apply(&mut self, write: char, mov: &Move) {
+        self.write_at(self.head, write);
+        self.move_head(mov);
     }
 
     pub fn head(&self) -> isize {
-        self.index - self.head_offset
+        self.head
     }
 
+    /// position of the head in `data`
     pub fn index(&self) -> usize {
-            self.index as usize
+        (self.head - self.left) as usize
     }
 
-    pub fn data(&self) -> &Vec<char> {
-        &self.data
+    /// position of the first cell of `data`
+    pub fn head_offset(&self) -> isize {
+        self.left
     }
 
-    pub fn set_head(&mut self, head: isize) {
-        self.index = head + self.head_offset;
-        self.extend();
-    }
-
-    fn extend(&mut self) {
-        match (self.index.cmp(&0), self.data.len().cmp(&((self.index + 1) as usize))) {
-            // padding left with spaces when index is less than 0.
-            // And increment head_offset by 1 (index must be gt 0 always).
-            (Ordering::Less, _) => {
-                self.data = [vec![SPACE; -self.index as usize], self.data.clone()].concat();
-                self.head_offset += self.index;
-                self.index = 0;
+    /// number of cells in `data`
+    pub fn data_len(&self) -> usize {
+        (self.right - self.left + 1) as usize
+    }
+
+    /// cells from the leftmost to the rightmost one which held data or were visited by the head
+    #[cfg(test)]
+    pub(crate) fn data(&self) -> Vec<char> {
+        (self.left..=self.right).map(|position| self.get(position)).collect()
+    }
+
+    /// the data without the stretches of more than `CHUNK_SIZE` blanks, as runs of cells with the position
+    /// of their first cell. The runs hold the non-blank cells, the head and both ends of the data,
+    /// so they stay as small as the written part of the tape however far the head went.
+    pub fn runs(&self) -> Vec<(isize, Vec<char>)> {
+        self.spans().into_iter()
+            .map(|(start, end)| (start, (start..=end).map(|position| self.get(position)).collect()))
+            .collect()
+    }
+
+    /// the leftmost and the rightmost non-blank cells or the head, the part of the tape which can't be told from blanks
+    pub fn content_bounds(&self) -> (isize, isize) {
+        let spans = self.chunk_spans();
+        let first = spans.first().map_or(self.head, |span| min(span.0, self.head));
+        let last = spans.last().map_or(self.head, |span| max(span.1, self.head));
+        (first, last)
+    }
+
+    /// the data has the cells of the pattern one after another. Only the runs are searched,
+    /// and the stretches of blanks between them by their length
+    pub(crate) fn contains(&self, pattern: &[char]) -> bool {
+        let len = pattern.len() as isize;
+        let spans = self.spans();
+        if pattern.iter().all(|c| *c == self.blank) && spans.windows(2).any(|w| w[1].0 - w[0].1 > len) {
+            return true;
+        }
+        // a match which isn't all in a stretch of blanks overlaps a run
+        spans.iter().any(|(start, end)| {
+            let (first, last) = (max(start - len + 1, self.left), min(end + len - 1, self.right));
+            (first..=last - len + 1).any(|position| (position..).zip(pattern).all(|(p, c)| self.get(p) == *c))
+        })
+    }
+
+    /// restores a tape from its runs, see `runs`
+    pub(crate) fn from_runs(runs: Vec<(isize, Vec<char>)>, head: isize, blank: char) -> Tape {
+        let mut tape = Tape { blank, chunks: BTreeMap::new(), head, left: head, right: head };
+        for (start, cells) in runs {
+            if !cells.is_empty() {
+                tape.left = min(tape.left, start);
+                tape.right = max(tape.right, start + cells.len() as isize - 1);
             }
-            // padding right with spaces when index is greater than data.len()-1.
-            (Ordering::Greater, Ordering::Less) => {
-                self.data = [self.data.clone(), vec![SPACE; (self.index - self.data.len() as isize + 1) as usize]].concat();
+            for (position, c) in (start..).zip(cells) {
+                tape.write_at(position, c);
             }
-            // do nothing when index is between 0 and data.len()-1.
-            (_, _) => (),
         }
+        tape
     }
 
-    fn write(&mut self, c: char) {
-        self.data[self.index as usize] = c;
+    /// positions of the first and the last non-blank cells of every stored chunk, from left to right
+    fn chunk_spans(&self) -> Vec<(isize, isize)> {
+        self.chunks.iter()
+            .map(|(index, chunk)| {
+                // a stored chunk has a non-blank cell
+                let first = chunk.iter().position(|c| *c != self.blank).unwrap_or(0) as isize;
+                let last = chunk.iter().rposition(|c| *c != self.blank).unwrap_or(0) as isize;
+                (index * CHUNK_SIZE + first, index * CHUNK_SIZE + last)
+            })
+            .collect()
+    }
+
+    /// positions of the first and the last cells of the runs
+    fn spans(&self) -> Vec<(isize, isize)> {
+        let mut spans = self.chunk_spans();
+        spans.extend([(self.left, self.left), (self.head, self.head), (self.right, self.right)]);
+        spans.sort_unstable();
+        let mut runs: Vec<(isize, isize)> = Vec::new();
+        for (start, end) in spans {
+            match runs.last_mut() {
+                // a short stretch of blanks stays in the run
+                Some(run) if start - run.1 - 1 <= CHUNK_SIZE => run.1 = max(run.1, end),
+                _ => runs.push((start, end)),
+            }
+        }
+        runs
+    }
+
+    pub fn set_head(&mut self, head: isize) {
+        self.head = head;
+        self.left = min(self.left, head);
+        self.right = max(self.right, head);
+    }
+
+    /// the leftmost and the rightmost cells of `data`
+    pub(crate) fn extent(&self) -> (isize, isize) {
+        (self.left, self.right)
+    }
+
+    /// moves the head back to `head`, puts back the symbol overwritten there and the extent of `data` before the step
+    pub(crate) fn restore(&mut self, head: isize, symbol: char, (left, right): (isize, isize)) {
+        self.head = head;
+        self.write_at(head, symbol);
+        self.left = left;
+        self.right = right;
+    }
+
+    /// hashes the head position and the non-blank cells with their positions.
+    /// Tapes which differ only in blank padding get the same hash.
+    pub(crate) fn hash_content<H: Hasher>(&self, hasher: &mut H) {
+        self.head.hash(hasher);
+        for (chunk_index, chunk) in &self.chunks {
+            for (position, c) in (chunk_index * CHUNK_SIZE..).zip(chunk.iter()).filter(|(_, c)| **c != self.blank) {
+                (position, c).hash(hasher);
+            }
+        }
+    }
+
+    fn write_at(&mut self, position: isize, c: char) {
+        let chunk_index = position.div_euclid(CHUNK_SIZE);
+        let offset = position.rem_euclid(CHUNK_SIZE) as usize;
+        match self.chunks.get_mut(&chunk_index) {
+            Some(chunk) => {
+                chunk[offset] = c;
+                // blank chunks are not stored
+                if c == self.blank && chunk.iter().all(|c| *c == self.blank) {
+                    self.chunks.remove(&chunk_index);
+                }
+            }
+            None if c != self.blank => {
+                let mut chunk = Box::new([self.blank; CHUNK_SIZE as usize]);
+                chunk[offset] = c;
+                self.chunks.insert(chunk_index, chunk);
+            }
+            None => (),
+        }
     }
 
     fn move_head(&mut self, mov: &Move) {
         match mov {
-            Move::Right => self.index += 1,
-            Move::Left => self.index -= 1,
+            Move::Right => self.set_head(self.head + 1),
+            Move::Left => self.set_head(self.head - 1),
             Move::Stop => (),
         }
     }
 }
 
 impl fmt::Display for Tape {
+    /// every cell of the data, the head in brackets. The alternate form `{:#}` writes only the runs of the data,
+    /// a long stretch of blanks between them is written as the blank and its length, like ` _×1000 `
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let tape = self.data.iter().enumerate().map(|(i, c)| {
-            if i == self.index as usize {
-                format!("[{}]", c)
-            } else {
-                format!(" {} ", c)
+        let spans = if f.alternate() { self.spans() } else { vec![(self.left, self.right)] };
+        let mut previous: Option<isize> = None;
+        for (start, end) in spans {
+            if let Some(previous) = previous {
+                write!(f, " {}×{} ", self.blank, start - previous - 1)?;
             }
-        }).collect::<Vec<String>>().join("");
-        write!(f, "{}", tape)
+            for position in start..=end {
+                if position == self.head {
+                    write!(f, "[{}]", self.get(position))?;
+                } else {
+                    write!(f, " {} ", self.get(position))?;
+                }
+            }
+            previous = Some(end);
+        }
+        Ok(())
     }
 }
 
@@ -116,6 +265,8 @@ impl FromStr for Tape {
 
 #[cfg(test)]
 mod test {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::Hasher;
     use crate::{Move, Rule, Tape};
     use crate::tape::SPACE;
 
@@ -138,11 +289,97 @@ mod test {
         test_empty_tape(Tape::new("".chars().collect(), -10, 1));
         test_empty_tape(Tape::new("A".chars().collect(), -10, 1));
         test_empty_tape(Tape::new("".chars().collect(), 0, 0));
+        let mut tape = Tape::new("AB".chars().collect(), -3, 0);
+        assert_eq!(tape.head(), -3);
+        tape.set_head(1);
+        assert_eq!(tape.read(), 'B');
         let mut tape = Tape::new("".chars().collect(), -10, -1);
         tape.set_head(15);
         test_empty_tape(tape);
     }
 
+    #[test]
+    fn test_hash_content() {
+        let hash = |tape: &Tape| {
+            let mut hasher = DefaultHasher::new();
+            tape.hash_content(&mut hasher);
+            hasher.finish()
+        };
+        let tape = Tape::new("ab".chars().collect(), 1, 0);
+        assert_eq!(tape.head(), 1);
+        let mut padded = Tape::new("__ab__".chars().collect(), 1, -2);
+        assert_eq!(padded.head(), 1);
+        assert_eq!(hash(&tape), hash(&padded));
+        padded.set_head(0);
+        assert_eq!(padded.head(), 0);
+        assert_eq!(padded.read(), 'a');
+        assert_ne!(hash(&tape), hash(&padded));
+        assert_ne!(hash(&tape), hash(&Tape::new("ab".chars().collect(), 2, 1)));
+        assert_ne!(hash(&tape), hash(&Tape::new("ba".chars().collect(), 1, 0)));
+    }
+
+    #[test]
+    fn test_tape_grows_both_ways() {
+        let mut tape = Tape::new("ab".chars().collect(), 0, 0);
+        for _ in 0..100_000 {
+            tape.apply(SPACE, &Move::Left);
+        }
+        assert_eq!((tape.head(), tape.index(), tape.head_offset()), (-100_000, 0, -100_000));
+        assert_eq!(tape.data().len(), 100_002);
+        for _ in 0..200_000 {
+            tape.apply(tape.read(), &Move::Right);
+        }
+        assert_eq!((tape.head(), tape.index()), (100_000, 200_000));
+        assert_eq!(tape.data().len(), 200_001);
+        tape.set_head(1);
+        assert_eq!(tape.read(), 'b');
+    }
+
+    #[test]
+    fn test_tape_sparse() {
+        let mut tape = Tape::new("ab".chars().collect(), 0, 0);
+        tape.set_head(1_000_000_000);
+        assert_eq!(tape.read(), SPACE);
+        tape.apply_rule(&Rule::new('c', Move::Left, 0));
+        assert_eq!(tape.chunks.len(), 2);
+        assert_eq!((tape.get(1), tape.get(1_000_000_000)), ('b', 'c'));
+        assert_eq!((tape.index(), tape.data_len()), (999_999_999, 1_000_000_001));
+        assert_eq!(format!("{:#}", tape), " a  b  _×999999997 [_] c ");
+        assert_eq!(tape.runs(), vec![(0, vec!['a', 'b']), (999_999_999, vec![SPACE, 'c'])]);
+        assert_eq!(tape.content_bounds(), (0, 1_000_000_000));
+        let restored = Tape::from_runs(tape.runs(), tape.head(), SPACE);
+        assert_eq!((format!("{:#}", restored), restored.data_len()), (format!("{:#}", tape), tape.data_len()));
+        tape.set_head(0);
+        tape.apply_rule(&Rule::new(SPACE, Move::Right, 0));
+        tape.apply_rule(&Rule::new(SPACE, Move::Right, 0));
+        // the chunk of the erased word is dropped
+        assert_eq!(tape.chunks.len(), 1);
+        assert_eq!(tape.head(), 2);
+    }
+
+    #[test]
+    fn test_tape_display() {
+        let mut tape = Tape::new("ab".chars().collect(), 0, 0);
+        tape.set_head(100);
+        assert_eq!(tape.to_string(), format!(" a  b {}[_]", " _ ".repeat(98)));
+        assert_eq!(format!("{:#}", tape), " a  b  _×98 [_]");
+        // a short stretch of blanks is written cell by cell in both forms
+        assert_eq!(format!("{:#}", Tape::new("ab".chars().collect(), 10, 0)), " a  b  _  _  _  _  _  _  _  _ [_]");
+    }
+
+    #[test]
+    fn test_tape_blank() {
+        let mut tape = Tape::with_blank("a_".chars().collect(), 0, 0, 'λ');
+        assert_eq!(tape.blank(), 'λ');
+        tape.apply_rule(&Rule::new('λ', Move::Left, 0));
+        assert_eq!(tape.read(), 'λ');
+        assert_eq!(tape.to_string(), "[λ] λ  _ ");
+        assert_eq!(tape.chunks.len(), 1);
+        tape.set_head(1);
+        tape.apply_rule(&Rule::new('λ', Move::Stop, 0));
+        assert!(tape.chunks.is_empty());
+    }
+
     fn test_empty_tape(mut tape: Tape) {
         assert_eq!(tape.read(), SPACE);
         tape.apply_rule(&Rule::new('h', Move::Right, 0));
@@ -1,18 +1,40 @@
-use crate::rule::{Rule, RuleState};
+use crate::rule::{Move, Rule, RuleState, WILDCARD};
 use crate::tape::Tape;
+use crate::turing::{Turing, TuringError};
 
+/// A step of the machine. Only the changes of the tapes are kept, see `History` to get the tapes before a step.
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Transition {
     state: RuleState,
-    tape: Tape,
+    deltas: Vec<Delta>,
     pub(crate) rule: Rule,
 }
 
+/// What a transition does with one tape: writes `new` over `old` at `head` and moves the head.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Delta {
+    head: isize,
+    old: char,
+    new: char,
+    mov: Move,
+    // the leftmost and the rightmost cells of the data before the transition, to revert a move onto a new cell
+    extent: (isize, isize),
+}
+
 impl Transition {
-    pub(crate) fn new(state: RuleState, tape: Tape, rule: Rule) -> Transition {
+    /// the transition made by the rule in `state` on `tapes`
+    pub(crate) fn new(state: RuleState, tapes: &[Tape], rule: Rule) -> Transition {
+        let deltas = tapes.iter().zip(rule.writes.iter().zip(rule.moves.iter()))
+            .map(|(tape, (write, mov))| {
+                let new = if *write == WILDCARD { tape.read() } else { *write };
+                Delta { head: tape.head(), old: tape.read(), new, mov: *mov, extent: tape.extent() }
+            })
+            .collect();
         Transition {
             state,
-            tape,
+            deltas,
             rule,
         }
     }
@@ -21,11 +43,76 @@ impl Transition {
         &self.state
     }
 
-    pub fn tape(&self) -> &Tape {
-        &self.tape
+    /// changes of every tape
+    pub fn deltas(&self) -> &Vec<Delta> {
+        &self.deltas
+    }
+
+    /// symbols under the heads before the transition
+    pub fn symbols(&self) -> Vec<char> {
+        self.deltas.iter().map(|d| d.old).collect()
     }
 
     pub fn rule(&self) -> &Rule {
         &self.rule
     }
+
+    /// makes the transition on the tapes it was made on
+    pub fn apply(&self, tapes: &mut [Tape]) {
+        for (tape, delta) in tapes.iter_mut().zip(self.deltas.iter()) {
+            tape.set_head(delta.head);
+            tape.apply(delta.new, &delta.mov);
+        }
+    }
+
+    /// reverts the transition on the tapes it was applied to
+    pub fn revert(&self, tapes: &mut [Tape]) {
+        for (tape, delta) in tapes.iter_mut().zip(self.deltas.iter()) {
+            tape.restore(delta.head, delta.old, delta.extent);
+        }
+    }
+}
+
+impl Delta {
+    /// head position before the transition
+    pub fn head(&self) -> isize {
+        self.head
+    }
+
+    pub fn old_symbol(&self) -> char {
+        self.old
+    }
+
+    pub fn new_symbol(&self) -> char {
+        self.new
+    }
+
+    pub fn mov(&self) -> Move {
+        self.mov
+    }
+}
+
+/// Iterator over the steps of a machine, see `Turing::transitions`.
+pub struct Transitions<'a> {
+    turing: &'a mut Turing,
+    failed: bool,
+}
+
+impl<'a> Transitions<'a> {
+    pub(crate) fn new(turing: &'a mut Turing) -> Transitions<'a> {
+        Transitions { turing, failed: false }
+    }
 }
+
+impl Iterator for Transitions<'_> {
+    type Item = Result<Transition, TuringError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed || self.turing.is_halted() || self.turing.detected_loop().is_some() {
+            return None;
+        }
+        let step = self.turing.step();
+        self.failed = step.is_err();
+        Some(step)
+    }
+}
\ No newline at end of file
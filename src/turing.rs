@@ -1,18 +1,43 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
 use std::error::Error;
 use std::fmt::Display;
+use std::hash::{Hash, Hasher};
+use crate::breakpoint::Breakpoint;
+use crate::observer::Observer;
+use crate::outcome::{Outcome, Run, Verdict};
 use crate::rule::{RuleState};
 use crate::tape::Tape;
-use crate::transition::Transition;
+use crate::transition::{Transition, Transitions};
 use crate::turing::TuringError::RuleNotFound;
 use crate::ruleset::{Ruleset, RulesetError};
+use crate::statistics::Statistics;
 
 pub struct Turing {
     state: RuleState,
-    tape: Tape,
+    tapes: Vec<Tape>,
     rules: Ruleset,
+    steps: usize,
+    halted: bool,
+    // hashes of the visited configurations with the step they were seen at, when loop detection is on
+    seen: Option<HashMap<u64, usize>>,
+    detected_loop: Option<(usize, usize)>,
+    undo_log: VecDeque<Undo>,
+    undo_limit: usize,
+    breakpoints: Vec<Breakpoint>,
+    hit_breakpoint: Option<Breakpoint>,
+    observers: Vec<Box<dyn Observer>>,
+    statistics: Option<Statistics>,
+}
+
+/// a step with the halt flag before it, enough to revert the step
+struct Undo {
+    transition: Transition,
+    halted: bool,
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TuringError {
     RuleNotFound {
         rule_error: RulesetError,
@@ -21,23 +46,253 @@ pub enum TuringError {
 
 
 impl Turing {
-    pub fn new(tape: Tape, state: RuleState, rules: Ruleset) -> Turing {
-        Turing { state, tape, rules }
+    /// creates a single-tape machine, see `with_tapes`
+    pub fn new(tape: Tape, state: impl Into<RuleState>, rules: Ruleset) -> Turing {
+        Turing::with_tapes(vec![tape], state, rules)
+    }
+
+    /// creates a multi-tape machine. The ruleset must be written for the same number of tapes,
+    /// and the tapes should use the blank of the ruleset.
+    pub fn with_tapes(tapes: Vec<Tape>, state: impl Into<RuleState>, rules: Ruleset) -> Turing {
+        let state = state.into();
+        let halted = rules.is_halting_state(&state);
+        Turing { state, tapes, rules, steps: 0, halted, seen: None, detected_loop: None, undo_log: VecDeque::new(), undo_limit: 0, breakpoints: vec![], hit_breakpoint: None, observers: vec![], statistics: None }
+    }
+
+    /// sets the step counter and the halt flag of a machine restored from a snapshot
+    pub(crate) fn restore_progress(&mut self, steps: usize, halted: bool) {
+        self.steps = steps;
+        self.halted = halted;
+    }
+
+    /// adds an observer notified by every `step`. Wrap it into `Rc<RefCell<_>>` to read it after the run.
+    pub fn add_observer(&mut self, observer: Box<dyn Observer>) {
+        self.observers.push(observer);
+    }
+
+    fn notify(&mut self, notify: impl Fn(&mut dyn Observer, &Turing)) {
+        // observers get the machine, so they are taken out of it for the time of the notification
+        let mut observers = std::mem::take(&mut self.observers);
+        for observer in observers.iter_mut() {
+            notify(observer.as_mut(), self);
+        }
+        self.observers = observers;
+    }
+
+    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) {
+        self.breakpoints.push(breakpoint);
+    }
+
+    pub fn remove_breakpoint(&mut self, breakpoint: &Breakpoint) {
+        self.breakpoints.retain(|b| b != breakpoint);
+    }
+
+    pub fn breakpoints(&self) -> &Vec<Breakpoint> {
+        &self.breakpoints
+    }
+
+    /// the breakpoint matched by the configuration after the last step, or the one reported by `run` before the first step
+    pub fn hit_breakpoint(&self) -> Option<&Breakpoint> {
+        self.hit_breakpoint.as_ref()
+    }
+
+    /// keeps the changes of the last `limit` steps so they can be reverted by `step_back`. The log is off by default.
+    pub fn set_undo_limit(&mut self, limit: usize) {
+        self.undo_limit = limit;
+        while self.undo_log.len() > limit {
+            self.undo_log.pop_front();
+        }
+    }
+
+    /// reverts the last step. Returns false when there is nothing to revert.
+    pub fn step_back(&mut self) -> bool {
+        let Some(undo) = self.undo_log.pop_back() else {
+            return false;
+        };
+        // forget the configuration we leave, so it is not taken for a loop when the machine comes back to it
+        let hash = self.seen.is_some().then(|| self.configuration_hash());
+        if let (Some(seen), Some(hash)) = (self.seen.as_mut(), hash) {
+            if seen.get(&hash) == Some(&self.steps) {
+                seen.remove(&hash);
+            }
+        }
+        undo.transition.revert(&mut self.tapes);
+        self.state = undo.transition.state().clone();
+        self.halted = undo.halted;
+        self.steps -= 1;
+        if self.detected_loop.is_some_and(|(_, repeated_at)| repeated_at > self.steps) {
+            self.detected_loop = None;
+        }
+        true
+    }
+
+    /// number of steps which can be reverted
+    pub fn undo_len(&self) -> usize {
+        self.undo_log.len()
+    }
+
+    /// turns on detection of infinite loops. Every configuration (the state, the head positions and the non-blank
+    /// tape contents) is hashed after each step, and the first configuration which comes back is reported by `detected_loop`.
+    pub fn detect_loops(&mut self, enabled: bool) {
+        self.seen = None;
+        self.detected_loop = None;
+        if enabled {
+            self.seen = Some(HashMap::from([(self.configuration_hash(), self.steps)]));
+        }
+    }
+
+    /// steps `(first_seen, repeated_at)` of the first repeated configuration. The loop period is `repeated_at - first_seen`.
+    pub fn detected_loop(&self) -> Option<(usize, usize)> {
+        self.detected_loop
+    }
+
+    /// turns on the collection of the statistics of the following steps, see `Statistics`.
+    /// Steps reverted by `step_back` stay counted.
+    pub fn collect_statistics(&mut self, enabled: bool) {
+        self.statistics = enabled.then(|| Statistics::new(self.state.clone(), &self.tapes));
+    }
+
+    pub fn statistics(&self) -> Option<&Statistics> {
+        self.statistics.as_ref()
+    }
+
+    fn configuration_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.state.hash(&mut hasher);
+        for tape in &self.tapes {
+            tape.hash_content(&mut hasher);
+        }
+        hasher.finish()
     }
 
     pub fn next_transition(&self) -> Result<Transition, TuringError> {
-        let current_symbol = self.tape.read();
-        let rule = self.rules.find(&self.state, &current_symbol).map_err(|e| RuleNotFound { rule_error: e})?;
-        Ok(Transition::new(self.state, self.tape.clone(), rule))
+        let rule = self.rules.find_symbols(&self.state, &self.read()).map_err(|e| RuleNotFound { rule_error: e})?;
+        Ok(Transition::new(self.state.clone(), &self.tapes, rule))
     }
 
     pub fn apply_transition(&mut self, transition: &Transition) {
-        self.state = transition.rule.next_state;
-        self.tape.apply_rule(&transition.rule);
+        if self.undo_limit > 0 {
+            if self.undo_log.len() == self.undo_limit {
+                self.undo_log.pop_front();
+            }
+            self.undo_log.push_back(Undo { transition: transition.clone(), halted: self.halted });
+        }
+        self.state = transition.rule.next_state.clone();
+        transition.apply(&mut self.tapes);
+        self.steps += 1;
+        if let Some(statistics) = self.statistics.as_mut() {
+            statistics.record(transition.state(), transition.symbols(), &self.state, &self.tapes);
+        }
+        self.halted = transition.rule.is_terminal() || self.rules.is_halting_state(&self.state);
+        self.hit_breakpoint = self.breakpoints.iter().find(|b| b.matches(&self.state, &self.tapes)).cloned();
+        // a halting rule which changes nothing repeats the configuration, but doesn't loop
+        if !self.halted && self.detected_loop.is_none() && self.seen.is_some() {
+            let hash = self.configuration_hash();
+            let steps = self.steps;
+            if let Some(first_seen) = self.seen.as_mut().and_then(|seen| seen.insert(hash, steps)) {
+                self.detected_loop = Some((first_seen, steps));
+            }
+        }
+    }
+
+    /// finds and applies the next transition. Returns the applied transition.
+    pub fn step(&mut self) -> Result<Transition, TuringError> {
+        let transition = match self.next_transition() {
+            Ok(transition) => transition,
+            Err(e) => {
+                self.notify(|observer, turing| observer.on_error(turing, &e));
+                return Err(e);
+            }
+        };
+        self.notify(|observer, turing| observer.before_step(turing, &transition));
+        self.apply_transition(&transition);
+        self.notify(|observer, turing| observer.after_step(turing, &transition));
+        if self.halted {
+            self.notify(|observer, turing| observer.on_halt(turing));
+        }
+        Ok(transition)
     }
 
+    /// iterates over the steps of the machine. Every item is the applied transition or the error of the step.
+    /// The iterator ends when the machine halts, after the first error, or at the first repeated configuration
+    /// when loop detection is on.
+    pub fn transitions(&mut self) -> Transitions<'_> {
+        Transitions::new(self)
+    }
+
+    /// runs the machine until it halts, but at most `max_steps` steps.
+    /// A missing rule ends the run with `Outcome::MissingRule`, other errors are returned as is.
+    /// With loop detection on, the run stops at the first repeated configuration.
+    /// The run also stops at a breakpoint, before the first step when the configuration already matches one,
+    /// and the outcome tells which. Running again continues from there.
+    pub fn run(&mut self, max_steps: usize) -> Result<Run, TuringError> {
+        let mut steps = 0;
+        // a breakpoint reported by the last run doesn't stop this one
+        if !self.halted && self.hit_breakpoint.is_none() {
+            self.hit_breakpoint = self.breakpoints.iter().find(|b| b.matches(&self.state, &self.tapes)).cloned();
+            if let Some(breakpoint) = self.hit_breakpoint.clone() {
+                return Ok(Run::new(Outcome::BreakpointHit { breakpoint }, steps, self.verdict()));
+            }
+        }
+        while !self.halted {
+            if let Some((first_seen, repeated_at)) = self.detected_loop {
+                return Ok(Run::new(Outcome::LoopDetected { first_seen, repeated_at }, steps, self.verdict()));
+            }
+            if steps >= max_steps {
+                return Ok(Run::new(Outcome::StepLimitReached, steps, self.verdict()));
+            }
+            match self.step() {
+                Ok(_) if self.halted => steps += 1,
+                Ok(_) => {
+                    steps += 1;
+                    if let Some(breakpoint) = self.hit_breakpoint.clone() {
+                        return Ok(Run::new(Outcome::BreakpointHit { breakpoint }, steps, self.verdict()));
+                    }
+                }
+                Err(RuleNotFound { rule_error: RulesetError::RuleNotFound { state, symbols } }) => {
+                    return Ok(Run::new(Outcome::MissingRule { state, symbols }, steps, self.verdict()));
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(Run::new(Outcome::Halted, steps, self.verdict()))
+    }
+
+    /// verdict of a machine with accept or reject states. It accepts or rejects when it enters such a state.
+    /// A machine with accept states also rejects when it halts in any other state or gets stuck without a rule.
+    pub fn verdict(&self) -> Option<Verdict> {
+        if self.rules.accept_states().contains(&self.state) {
+            Some(Verdict::Accept)
+        } else if self.rules.reject_states().contains(&self.state)
+            || (self.rules.is_recognizer() && (self.halted || self.rules.find_all(&self.state, &self.read()).is_empty())) {
+            Some(Verdict::Reject)
+        } else {
+            None
+        }
+    }
+
+    /// the last applied rule was a terminal one or the machine entered an accept or a reject state
+    pub fn is_halted(&self) -> bool {
+        self.halted
+    }
+
+    /// number of transitions applied since the machine was created
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    /// symbols under every head
+    pub fn read(&self) -> Vec<char> {
+        self.tapes.iter().map(Tape::read).collect()
+    }
+
+    /// the first tape
     pub fn tape (&self) -> &Tape {
-        &self.tape
+        &self.tapes[0]
+    }
+
+    pub fn tapes(&self) -> &Vec<Tape> {
+        &self.tapes
     }
 
     pub fn ruleset (&self) -> &Ruleset {
@@ -45,7 +300,7 @@ impl Turing {
     }
 
     pub fn state(&self) -> RuleState {
-        self.state
+        self.state.clone()
     }
 }
 
@@ -60,17 +315,20 @@ impl Display for TuringError {
 impl Error for TuringError {}
 
 #[cfg(test)]
-mod test {
+pub(crate) mod test {
+    use crate::breakpoint::Breakpoint;
+    use crate::outcome::{Outcome, Run, Verdict};
     use crate::ruleset::Ruleset;
     use crate::tape::Tape;
-    use crate::turing::Turing;
+    use crate::observer::Observer;
+    use crate::transition::Transition;
+    use crate::turing::{Turing, TuringError};
+    use std::cell::RefCell;
+    use std::rc::Rc;
     use std::str::FromStr;
 
-    #[test]
-    fn test_turing() {
-        // calculating 5x+y. On the tape in writes as "x+y"
-        let rules = Ruleset::from_str(
-            "\
+    // calculating 5x+y. On the tape in writes as "x+y"
+    pub(crate) const FIVE_X_PLUS_Y: &str = "\
 |  | 0 | 1 | 2 | 3 | 4 | 5 | 6 | 7 | 8 | 9 | 10 |
 | :--- | :--- | :--- | :--- | :--- | :--- | :--- | :--- | :--- | :--- | :--- | :--- |
 | 1 | 5<0 | 6<0 | 7<0 | 8<0 | 9<0 | 0<6 | 1<6 | 2>8 | 1>8 | 1>8 | 1!0 |
@@ -85,20 +343,253 @@ mod test {
 | 0 | 0<0 | 1<0 | 2<0 | 3<0 | 4<0 | 9<5 | 0<6 | 1>8 | 0>8 | +>9 | 0!0 |
 | + | +!0 | +!0 | +!0 | +!0 | +!0 | _<10 | +<7 | +<7 | +>9 | +>9 | _<10 |
 | _ | _>8 | 1<0 | 2<0 | 3<0 | 4<0 | _!0 | _!0 | 1>8 | _<5 | _<5 | _!0 |\
-").expect("Invalid ruleset");
+";
+
+    #[test]
+    fn test_turing() {
+        let rules = Ruleset::from_str(FIVE_X_PLUS_Y).expect("Invalid ruleset");
         let tape = Tape::new("123+19".chars().collect(), 2, 0);
         let mut turing = Turing::new(tape, 0, rules);
-        let mut limit = 1000;
-        while limit > 0 {
-            let transition = turing.next_transition().expect("Error in transition");
-            turing.apply_transition(&transition);
-            if transition.rule().mov().is_terminal() {
-                break;
-            }
-            limit -= 1;
-        }
+        let run = turing.run(1000).expect("Error in transition");
         let tape = turing.tape();
         assert_eq!(tape.data().iter().collect::<String>(), "_634____");
-        assert_eq!(limit, 1000-170);
+        assert_eq!(run.outcome(), &Outcome::Halted);
+        assert_eq!(run.steps(), 171);
+        assert!(turing.is_halted());
+        assert_eq!(turing.run(1000).unwrap().steps(), 0);
+    }
+
+    #[test]
+    fn test_run_limits() {
+        let rules = Ruleset::from_str(
+            "\
+|   | 0   | 1   |
+|:-:|:-:  |:-:  |
+| a | b>0 | a!1 |
+| b | a>1 | b<0 |\
+").expect("Invalid ruleset");
+        let mut turing = Turing::new(Tape::new("aab".chars().collect(), 0, 0), 0, rules.clone());
+        assert_eq!(turing.run(2).unwrap(), Run::new(Outcome::StepLimitReached, 2, None));
+        assert_eq!(turing.run(10).unwrap(), Run::new(Outcome::MissingRule { state: 1.into(), symbols: vec!['_'] }, 1, None));
+        assert_eq!(turing.steps(), 3);
+        assert!(!turing.is_halted());
+    }
+
+    #[test]
+    fn test_loop_detection() {
+        // walks right over "a" and back left over "b" forever
+        let rules = Ruleset::from_str(
+            "\
+|   | 0   | 1   |
+|:-:|:-:  |:-:  |
+| a | a>0 | a>0 |
+| b | b<1 | b<1 |\
+").expect("Invalid ruleset");
+        let mut turing = Turing::new(Tape::new("xab".chars().collect(), 1, 0), 0, rules.clone());
+        assert_eq!(turing.run(100).unwrap().outcome(), &Outcome::StepLimitReached);
+
+        let mut turing = Turing::new(Tape::new("xab".chars().collect(), 1, 0), 0, rules);
+        turing.detect_loops(true);
+        // step 0: state 0 on "a", step 1: state 0 on "b", step 2: state 1 on "a", step 3: state 0 on "b"
+        assert_eq!(turing.run(100).unwrap(), Run::new(Outcome::LoopDetected { first_seen: 1, repeated_at: 3 }, 3, None));
+        assert_eq!(turing.detected_loop(), Some((1, 3)));
+    }
+
+    #[test]
+    fn test_step_back() {
+        let rules = Ruleset::from_str(
+            "\
+|   | 0   | 1   |
+|:-:|:-:  |:-:  |
+| a | b<0 | a!1 |
+| b | b<0 | b!1 |
+| _ | x>1 | _>1 |\
+").expect("Invalid ruleset");
+        let mut turing = Turing::new(Tape::new("aa".chars().collect(), 1, 0), 0, rules);
+        assert!(!turing.step_back());
+        turing.set_undo_limit(3);
+        turing.detect_loops(true);
+        assert_eq!(turing.run(10).unwrap().outcome(), &Outcome::Halted);
+        assert_eq!(turing.steps(), 4);
+        assert_eq!(turing.tape().data().iter().collect::<String>(), "xbb");
+        assert_eq!(turing.undo_len(), 3);
+
+        assert!(turing.step_back());
+        assert!(!turing.is_halted());
+        assert_eq!((turing.state(), turing.tape().head(), turing.read()), (1.into(), 0, vec!['b']));
+        assert!(turing.step_back());
+        // the head is on the blank left of the input
+        assert_eq!((turing.state(), turing.tape().head(), turing.read()), (0.into(), -1, vec!['_']));
+        assert_eq!(turing.tape().data().iter().collect::<String>(), "_bb");
+        // the step which went onto that blank is reverted, the cell is not part of the data anymore
+        assert!(turing.step_back());
+        assert_eq!((turing.state(), turing.tape().head(), turing.read()), (0.into(), 0, vec!['a']));
+        assert_eq!(turing.tape().data().iter().collect::<String>(), "ab");
+        assert_eq!(turing.tape().to_string(), "[a] b ");
+        assert_eq!(turing.steps(), 1);
+        assert!(!turing.step_back());
+
+        // replaying after the rewind gives the same result, without a false loop
+        assert_eq!(turing.run(10).unwrap(), Run::new(Outcome::Halted, 3, None));
+        assert_eq!(turing.tape().data().iter().collect::<String>(), "xbb");
+    }
+
+    #[test]
+    fn test_breakpoints() {
+        let rules = Ruleset::from_str(FIVE_X_PLUS_Y).expect("Invalid ruleset");
+        let mut turing = Turing::new(Tape::new("123+19".chars().collect(), 2, 0), 0, rules);
+        turing.add_breakpoint(Breakpoint::StateSymbol(7.into(), '+'));
+        let run = turing.run(1000).unwrap();
+        assert_eq!(run.outcome(), &Outcome::BreakpointHit { breakpoint: Breakpoint::StateSymbol(7.into(), '+') });
+        assert_eq!((turing.state(), turing.tape().read()), (7.into(), '+'));
+        assert_eq!(turing.hit_breakpoint(), Some(&Breakpoint::StateSymbol(7.into(), '+')));
+
+        turing.remove_breakpoint(&Breakpoint::StateSymbol(7.into(), '+'));
+        let rest = turing.run(1000).unwrap();
+        assert_eq!(rest.outcome(), &Outcome::Halted);
+        assert_eq!(run.steps() + rest.steps(), 171);
+        assert_eq!(turing.hit_breakpoint(), None);
+    }
+
+    #[test]
+    fn test_breakpoint_at_start() {
+        let rules = Ruleset::from_str(FIVE_X_PLUS_Y).expect("Invalid ruleset");
+        let mut turing = Turing::new(Tape::new("123+19".chars().collect(), 2, 0), 0, rules);
+        turing.add_breakpoint(Breakpoint::StateSymbol(0.into(), '3'));
+        let run = turing.run(1000).unwrap();
+        assert_eq!(run, Run::new(Outcome::BreakpointHit { breakpoint: Breakpoint::StateSymbol(0.into(), '3') }, 0, None));
+        assert_eq!(turing.steps(), 0);
+
+        // the reported breakpoint doesn't stop the machine again
+        let rest = turing.run(1000).unwrap();
+        assert_eq!(rest.outcome(), &Outcome::Halted);
+        assert_eq!(rest.steps(), 171);
+    }
+
+    #[test]
+    fn test_verdict() {
+        // accepts words with an even number of "a"
+        let rules = Ruleset::from_str(
+            "\
+accept: 2
+reject: 3
+|   | 0   | 1   |
+|:-:|:-:  |:-:  |
+| a | a>1 | a>0 |
+| b | b!0 | b!1 |
+| _ | _>2 | _>3 |\
+").expect("Invalid ruleset");
+        let verdict = |input: &str| {
+            let mut turing = Turing::new(Tape::new(input.chars().collect(), 0, 0), 0, rules.clone());
+            assert_eq!(turing.verdict(), None);
+            let run = turing.run(100).unwrap();
+            assert_eq!(run.verdict(), turing.verdict());
+            (run.outcome().clone(), run.verdict())
+        };
+        assert_eq!(verdict("aa"), (Outcome::Halted, Some(Verdict::Accept)));
+        assert_eq!(verdict("aaa"), (Outcome::Halted, Some(Verdict::Reject)));
+        // halting outside of the accept states and a missing rule reject too
+        assert_eq!(verdict("aab"), (Outcome::Halted, Some(Verdict::Reject)));
+        assert_eq!(verdict("ac"), (Outcome::MissingRule { state: 1.into(), symbols: vec!['c'] }, Some(Verdict::Reject)));
+
+        let turing = Turing::new(Tape::new(vec![], 0, 0), 2, rules);
+        assert!(turing.is_halted());
+        assert_eq!(turing.verdict(), Some(Verdict::Accept));
+    }
+
+    #[test]
+    fn test_transitions() {
+        let rules = Ruleset::from_str(FIVE_X_PLUS_Y).expect("Invalid ruleset");
+        let mut turing = Turing::new(Tape::new("123+19".chars().collect(), 2, 0), 0, rules);
+        assert_eq!(turing.transitions().take(10).count(), 10);
+        let plus_reads = turing.transitions()
+            .map(|t| t.expect("Error in transition"))
+            .filter(|t| t.symbols() == ['+'])
+            .count();
+        assert_eq!(plus_reads, 59);
+        assert!(turing.is_halted());
+        assert_eq!(turing.steps(), 171);
+        assert!(turing.transitions().next().is_none());
+
+        let rules = Ruleset::from_str("|   | 0   |\n| a | a>0 |").expect("Invalid ruleset");
+        let mut turing = Turing::new(Tape::new("aa".chars().collect(), 0, 0), 0, rules);
+        let (i, last) = turing.transitions().enumerate().last().unwrap();
+        assert_eq!(i, 2);
+        assert!(last.is_err());
+        assert_eq!(turing.transitions().count(), 1);
+    }
+
+    #[derive(Default)]
+    struct Events(Vec<String>);
+
+    impl Observer for Events {
+        fn before_step(&mut self, turing: &Turing, transition: &Transition) {
+            self.0.push(format!("before {} {}", turing.steps(), transition.rule()));
+        }
+        fn after_step(&mut self, turing: &Turing, _transition: &Transition) {
+            self.0.push(format!("after {} {}", turing.steps(), turing.state()));
+        }
+        fn on_halt(&mut self, turing: &Turing) {
+            self.0.push(format!("halt {}", turing.steps()));
+        }
+        fn on_error(&mut self, _turing: &Turing, error: &TuringError) {
+            self.0.push(format!("error {}", error));
+        }
+    }
+
+    #[test]
+    fn test_observer() {
+        let rules = Ruleset::from_str("|   | 0   | 1   |\n| a | a>1 | a!1 |").expect("Invalid ruleset");
+        let events = Rc::new(RefCell::new(Events::default()));
+        let mut turing = Turing::new(Tape::new("aa".chars().collect(), 0, 0), 0, rules.clone());
+        turing.add_observer(Box::new(events.clone()));
+        turing.run(10).unwrap();
+        assert_eq!(events.borrow().0, vec!["before 0 a>1", "after 1 1", "before 1 a!1", "after 2 1", "halt 2"]);
+
+        let events = Rc::new(RefCell::new(Events::default()));
+        let mut turing = Turing::new(Tape::new("a".chars().collect(), 0, 0), 0, rules);
+        turing.add_observer(Box::new(events.clone()));
+        turing.run(10).unwrap();
+        assert_eq!(events.borrow().0, vec![
+            "before 0 a>1".to_string(),
+            "after 1 1".to_string(),
+            "error Rule not found: Rule for state \"1\" and symbol \"_\" not found".to_string(),
+        ]);
+    }
+
+    #[test]
+    fn test_keep_symbol_rules() {
+        // scans right over everything up to the blank and marks the end with a "$"
+        let rules = Ruleset::from_str("\
+|   | 0   |
+|:-:|:-:  |
+| _ | $!0 |
+| * | *>0 |").expect("Invalid ruleset");
+        let mut turing = Turing::new(Tape::new("5+x1".chars().collect(), 0, 0), 0, rules);
+        assert_eq!(turing.run(10).unwrap().outcome(), &Outcome::Halted);
+        assert_eq!(turing.steps(), 5);
+        assert_eq!(turing.tape().data().iter().collect::<String>(), "5+x1$");
+    }
+
+    #[test]
+    fn test_multi_tape_turing() {
+        // copies the first tape to the second one and returns both heads to the start
+        let rules = Ruleset::from_str(
+            "\
+|    | 0     | 1     | 2     |
+| :--- | :--- | :--- | :--- |
+| a_ | a>a>0 | a<a<1 | a!a!2 |
+| b_ | b>b>0 | b<b<1 | b!b!2 |
+| __ | _<_<1 | _>_>2 | _!_!2 |
+| aa | a>a>0 | a<a<1 | a!a!2 |
+| bb | b>b>0 | b<b<1 | b!b!2 |\
+").expect("Invalid ruleset");
+        let tapes = vec![Tape::new("abba".chars().collect(), 0, 0), Tape::new(vec![], 0, 0)];
+        let mut turing = Turing::with_tapes(tapes, 0, rules);
+        assert_eq!(turing.run(1000).unwrap().steps(), 11);
+        assert_eq!(turing.tapes()[0].data().iter().collect::<String>(), "_abba_");
+        assert_eq!(turing.tapes()[1].data().iter().collect::<String>(), "_abba_");
+        assert_eq!(turing.read(), vec!['a', 'a']);
+        assert_eq!(turing.state(), 2.into());
     }
 }
\ No newline at end of file
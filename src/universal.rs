@@ -0,0 +1,332 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fmt::Display;
+use std::str::FromStr;
+use crate::rule::{Move, Rule, RuleState, WILDCARD};
+use crate::ruleset::Ruleset;
+use crate::tape::{Tape, SPACE};
+use crate::turing::Turing;
+
+/// Markdown table of the universal machine, see `Ruleset::universal`
+const UNIVERSAL: &str = include_str!("universal.md");
+
+/// Numbering of the states and the symbols of a single-tape machine, which writes the machine and its configuration
+/// as one string for the universal machine.
+///
+/// Every state and symbol gets a binary code of a fixed width, the blank is `0…0`. The string is the code of the current
+/// state, the code of the blank, the rules and the cells of the tape, separated by `#`.
+/// A rule is `state,symbol,next_state,write,move;` with the move `00` to stop, `01` to go left and `10` to go right.
+/// Every cell is its code after a `,`, the cell under the head is after a `^` instead:
+/// ```text
+/// 0#00#0,00,1,00,00;0,01,0,10,10;#,01^01
+/// ```
+/// is the machine in the state 0 on the tape `a[a]`, which replaces `a` with `b` going right and halts on the blank:
+/// the blank is `00`, `a` is `01` and `b` is `10`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Encoding {
+    states: Vec<RuleState>,
+    symbols: Vec<char>,
+    state_width: usize,
+    symbol_width: usize,
+    blank: char,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EncodingError {
+    /// only single-tape machines can be encoded
+    MultiTape { tapes: usize },
+    /// the cell has several rules
+    Nondeterministic { state: RuleState, symbol: char },
+    UnknownState { state: RuleState },
+    UnknownSymbol { symbol: char },
+    /// the string is not written by the encoding
+    InvalidCode { code: String },
+}
+
+impl Encoding {
+    /// numbers the states in the order of the table, then the states which have no column.
+    /// The blank goes first among the symbols, then the alphabet and then the symbols which are only written.
+    pub fn new(ruleset: &Ruleset) -> Result<Encoding, EncodingError> {
+        if ruleset.tapes() != 1 {
+            return Err(EncodingError::MultiTape { tapes: ruleset.tapes() });
+        }
+        let mut states = ruleset.states().clone();
+        let mut symbols = vec![ruleset.blank()];
+        let rules = ruleset.states().iter().flat_map(|state| ruleset.symbols().iter().flat_map(move |row| ruleset.cell(state, row)));
+        for symbol in ruleset.alphabet().iter().chain(rules.clone().map(|rule| &rule.writes[0])) {
+            if *symbol != WILDCARD && !symbols.contains(symbol) {
+                symbols.push(*symbol);
+            }
+        }
+        for state in rules.map(|rule| &rule.next_state).chain(ruleset.accept_states()).chain(ruleset.reject_states()) {
+            if !states.contains(state) {
+                states.push(state.clone());
+            }
+        }
+        Ok(Encoding {
+            state_width: width(states.len()),
+            symbol_width: width(symbols.len()),
+            states,
+            symbols,
+            blank: ruleset.blank(),
+        })
+    }
+
+    pub fn states(&self) -> &Vec<RuleState> {
+        &self.states
+    }
+
+    pub fn symbols(&self) -> &Vec<char> {
+        &self.symbols
+    }
+
+    /// writes the machine in the state on the tape. Wildcards are replaced with the symbols they match,
+    /// and the rules of the accept and the reject states are left out, because the machine halts in them.
+    /// Only the cells from the first to the last non-blank one or the head are written,
+    /// the universal machine adds blank cells when the head leaves them.
+    pub fn encode(&self, ruleset: &Ruleset, state: &RuleState, tape: &Tape) -> Result<String, EncodingError> {
+        let mut code = format!("{}#{}#", self.state_code(state)?, self.symbol_code(&self.blank)?);
+        for state in ruleset.states().iter().filter(|state| !ruleset.is_halting_state(state)) {
+            for symbol in &self.symbols {
+                let rule = match ruleset.find_all(state, &[*symbol]) {
+                    [] => continue,
+                    [rule] => rule,
+                    _ => return Err(EncodingError::Nondeterministic { state: state.clone(), symbol: *symbol }),
+                };
+                let write = if rule.write() == WILDCARD { *symbol } else { rule.write() };
+                let mov = match rule.mov() {
+                    Move::Stop => "00",
+                    Move::Left => "01",
+                    Move::Right => "10",
+                };
+                code.push_str(&format!(
+                    "{},{},{},{},{};",
+                    self.state_code(state)?,
+                    self.symbol_code(symbol)?,
+                    self.state_code(&rule.next_state)?,
+                    self.symbol_code(&write)?,
+                    mov,
+                ));
+            }
+        }
+        code.push('#');
+        let (first, last) = tape.content_bounds();
+        for position in first..=last {
+            code.push(if position == tape.head() { '^' } else { ',' });
+            code.push_str(&self.symbol_code(&tape.get(position))?);
+        }
+        Ok(code)
+    }
+
+    /// reads the machine, its state and its tape back from the string written by `encode`.
+    /// The rules of the machine are in the single row of every symbol, the accept and the reject states are not kept.
+    pub fn decode(&self, code: &str) -> Result<(Ruleset, RuleState, Tape), EncodingError> {
+        let invalid = |code: &str| EncodingError::InvalidCode { code: code.to_string() };
+        let sections: Vec<&str> = code.split('#').collect();
+        let [state, blank, rules, cells] = sections.as_slice() else {
+            return Err(invalid(code));
+        };
+        if self.symbol(blank)? != self.blank {
+            return Err(invalid(blank));
+        }
+        let mut table: HashMap<RuleState, HashMap<Vec<char>, Vec<Rule>>> = HashMap::new();
+        for rule in rules.split_terminator(';') {
+            let fields: Vec<&str> = rule.split(',').collect();
+            let [state, symbol, next_state, write, mov] = fields.as_slice() else {
+                return Err(invalid(rule));
+            };
+            let mov = match *mov {
+                "00" => Move::Stop,
+                "01" => Move::Left,
+                "10" => Move::Right,
+                _ => return Err(invalid(mov)),
+            };
+            table.entry(self.state(state)?).or_default()
+                .insert(vec![self.symbol(symbol)?], vec![Rule::new(self.symbol(write)?, mov, self.state(next_state)?)]);
+        }
+        let states = self.states.iter().filter(|state| table.contains_key(*state)).cloned().collect();
+        let rows = self.symbols.iter().filter(|symbol| table.values().any(|cells| cells.contains_key(&vec![**symbol]))).map(|symbol| vec![*symbol]).collect();
+        let mut ruleset = Ruleset::new(table, rows, states);
+        ruleset.set_blank(self.blank);
+        Ok((ruleset, self.state(state)?, self.tape(cells)?))
+    }
+
+    /// the state and the tape of the simulated machine, from the tapes of the universal machine running its code
+    pub fn decode_universal(&self, universal: &Turing) -> Result<(RuleState, Tape), EncodingError> {
+        let [_, cells, state, _] = universal.tapes().as_slice() else {
+            return Err(EncodingError::MultiTape { tapes: universal.tapes().len() });
+        };
+        let state: String = state.runs().into_iter().flat_map(|(_, cells)| cells).filter(|c| *c != SPACE).collect();
+        // the head of the universal machine is on a bit of the current cell or on the separator before it
+        let mut code = String::new();
+        let mut head = None;
+        let runs = cells.runs().into_iter().flat_map(|(start, run)| (start..).zip(run));
+        for (position, c) in runs.filter(|(_, c)| *c != SPACE) {
+            if c == ',' && position <= cells.head() {
+                head = Some(code.len());
+            }
+            code.push(c);
+        }
+        let head = head.ok_or_else(|| EncodingError::InvalidCode { code: code.clone() })?;
+        code.replace_range(head..=head, "^");
+        Ok((self.state(&state)?, self.tape(&code)?))
+    }
+
+    fn state_code(&self, state: &RuleState) -> Result<String, EncodingError> {
+        let index = self.states.iter().position(|s| s == state).ok_or_else(|| EncodingError::UnknownState { state: state.clone() })?;
+        Ok(format!("{:0width$b}", index, width = self.state_width))
+    }
+
+    fn symbol_code(&self, symbol: &char) -> Result<String, EncodingError> {
+        let index = self.symbols.iter().position(|s| s == symbol).ok_or(EncodingError::UnknownSymbol { symbol: *symbol })?;
+        Ok(format!("{:0width$b}", index, width = self.symbol_width))
+    }
+
+    fn state(&self, code: &str) -> Result<RuleState, EncodingError> {
+        decode_index(code, self.state_width).and_then(|i| self.states.get(i)).cloned()
+            .ok_or_else(|| EncodingError::InvalidCode { code: code.to_string() })
+    }
+
+    fn symbol(&self, code: &str) -> Result<char, EncodingError> {
+        decode_index(code, self.symbol_width).and_then(|i| self.symbols.get(i)).copied()
+            .ok_or_else(|| EncodingError::InvalidCode { code: code.to_string() })
+    }
+
+    /// the cells after `,` and the head cell after `^`
+    fn tape(&self, cells: &str) -> Result<Tape, EncodingError> {
+        let invalid = || EncodingError::InvalidCode { code: cells.to_string() };
+        let mut codes: Vec<String> = Vec::new();
+        let mut head = None;
+        for c in cells.chars() {
+            match c {
+                '^' | ',' => {
+                    if c == '^' {
+                        head = Some(codes.len());
+                    }
+                    codes.push(String::new());
+                }
+                bit => codes.last_mut().ok_or_else(invalid)?.push(bit),
+            }
+        }
+        let data = codes.iter().map(|code| self.symbol(code)).collect::<Result<Vec<char>, EncodingError>>()?;
+        let head = head.ok_or_else(invalid)?;
+        Ok(Tape::with_blank(data, head as isize, 0, self.blank))
+    }
+}
+
+/// bits for the numbers from 0 to `count - 1`
+fn width(count: usize) -> usize {
+    (usize::BITS - count.saturating_sub(1).leading_zeros()).max(1) as usize
+}
+
+fn decode_index(code: &str, width: usize) -> Option<usize> {
+    if code.len() != width {
+        return None;
+    }
+    usize::from_str_radix(code, 2).ok()
+}
+
+impl Ruleset {
+    /// the universal machine of four tapes. It runs the string written by `Encoding::encode` on the first tape,
+    /// keeps the cells of the simulated tape on the second one, its state on the third one, and the code of the blank on the last one.
+    /// The machine halts in the state `halt` when the simulated machine halts, and in `stuck` when it has no rule.
+    pub fn universal() -> Ruleset {
+        Ruleset::from_str(UNIVERSAL).expect("the universal machine is a valid ruleset")
+    }
+}
+
+impl Turing {
+    /// the universal machine which simulates this single-tape machine from its current configuration,
+    /// with the encoding to read the result by `Encoding::decode_universal`
+    pub fn to_universal(&self) -> Result<(Turing, Encoding), EncodingError> {
+        let encoding = Encoding::new(self.ruleset())?;
+        let code = encoding.encode(self.ruleset(), &self.state(), self.tape())?;
+        let universal = Ruleset::universal();
+        let tapes = vec![
+            Tape::new(code.chars().collect(), 0, 0),
+            Tape::new(vec![], 0, 0),
+            Tape::new(vec![], 0, 0),
+            Tape::new(vec![], 0, 0),
+        ];
+        Ok((Turing::with_tapes(tapes, universal.initial_state(), universal), encoding))
+    }
+}
+
+impl Display for EncodingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EncodingError::MultiTape { tapes } => write!(f, "Only single-tape machines can be encoded, the machine has {} tapes", tapes),
+            EncodingError::Nondeterministic { state, symbol } => write!(f, "Several rules for state \"{}\" and symbol \"{}\"", state, symbol),
+            EncodingError::UnknownState { state } => write!(f, "Unknown state: {}", state),
+            EncodingError::UnknownSymbol { symbol } => write!(f, "Unknown symbol: {}", symbol),
+            EncodingError::InvalidCode { code } => write!(f, "Invalid code: {}", code),
+        }
+    }
+}
+
+impl Error for EncodingError {}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+    use crate::ruleset::Ruleset;
+    use crate::tape::Tape;
+    use crate::turing::test::FIVE_X_PLUS_Y;
+    use crate::turing::Turing;
+    use crate::universal::{Encoding, EncodingError};
+
+    // replaces "a" with "b" going right and halts on the blank
+    const A_TO_B: &str = "\
+|   | 0   |
+|:-:|:-:  |
+| a | b>0 |
+| _ | _!1 |";
+
+    #[test]
+    fn test_encode_decode() {
+        let rules = Ruleset::from_str(A_TO_B).unwrap();
+        let encoding = Encoding::new(&rules).unwrap();
+        let code = encoding.encode(&rules, &0.into(), &Tape::new("aa".chars().collect(), 1, 0)).unwrap();
+        assert_eq!(code, "0#00#0,00,1,00,00;0,01,0,10,10;#,01^01");
+        let (decoded, state, tape) = encoding.decode(&code).unwrap();
+        assert_eq!(state, 0.into());
+        assert_eq!(tape.to_string(), Tape::new("aa".chars().collect(), 1, 0).to_string());
+        assert_eq!(decoded.find(&0.into(), &'a').unwrap(), rules.find(&0.into(), &'a').unwrap());
+        assert_eq!(decoded.find(&0.into(), &'_').unwrap(), rules.find(&0.into(), &'_').unwrap());
+        assert_eq!(encoding.decode("0#00#0,01;#,01").err(), Some(EncodingError::InvalidCode { code: "0,01".to_string() }));
+        assert!(matches!(encoding.encode(&rules, &0.into(), &Tape::new(vec!['c'], 0, 0)), Err(EncodingError::UnknownSymbol { symbol: 'c' })));
+    }
+
+    #[test]
+    fn test_encode_far_jump() {
+        let rules = Ruleset::from_str(A_TO_B).unwrap();
+        let encoding = Encoding::new(&rules).unwrap();
+        let mut tape = Tape::new("aa".chars().collect(), 1, 0);
+        tape.set_head(1_000_000_000);
+        tape.set_head(1);
+        // the visited blanks are not written
+        assert_eq!(encoding.encode(&rules, &0.into(), &tape).unwrap(), "0#00#0,00,1,00,00;0,01,0,10,10;#,01^01");
+        tape.set_head(-3);
+        assert!(encoding.encode(&rules, &0.into(), &tape).unwrap().ends_with("#^00,00,00,01,01"));
+    }
+
+    #[test]
+    fn test_universal_five_x_plus_y() {
+        let rules = Ruleset::from_str(FIVE_X_PLUS_Y).unwrap();
+        let mut direct = Turing::new(Tape::new("123+19".chars().collect(), 2, 0), 0, rules);
+        let (mut universal, encoding) = direct.to_universal().unwrap();
+        direct.run(1000).unwrap();
+        // the 171 steps of the machine take the universal machine 816401 steps
+        universal.run(820_000).unwrap();
+        assert!(universal.is_halted());
+        assert_eq!(universal.steps(), 816_401);
+        assert_eq!(universal.state().to_string(), "halt");
+        let (state, tape) = encoding.decode_universal(&universal).unwrap();
+        assert_eq!(state, direct.state());
+        assert_eq!(tape.data(), direct.tape().data());
+        assert_eq!(tape.index(), direct.tape().index());
+    }
+}
@@ -0,0 +1,539 @@
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+use std::fmt::Display;
+use std::str::FromStr;
+use yaml_rust2::{Yaml, YamlLoader};
+use crate::composition::unique;
+use crate::import::{state_name, Symbols};
+use crate::rule::{Move, Rule, RuleState, WILDCARD};
+use crate::ruleset::Ruleset;
+use crate::tape::{Tape, SPACE};
+use crate::turing::Turing;
+
+/// A single-tape machine in the YAML format of turingmachine.io: the input written on the tape from the head,
+/// the blank, the start state and the table of the instructions of every state for the symbols.
+/// An instruction moves the head `L` or `R`, optionally writing a symbol before and going to another state:
+/// ```text
+/// input: '1011'
+/// blank: ' '
+/// start state: right
+/// table:
+///   right:
+///     [1, 0]: R
+///     ' ': {L: carry}
+///   carry:
+///     1: {write: 0, L}
+///     [0, ' ']: {write: 1, L: done}
+///   done:
+/// ```
+/// The machine halts when its state has no instruction for the symbol under the head.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct YamlMachine {
+    ruleset: Ruleset,
+    state: RuleState,
+    tape: Tape,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum YamlError {
+    /// turingmachine.io machines have a single tape
+    MultiTape { tapes: usize },
+    /// the cell has several rules
+    Nondeterministic { state: RuleState, symbol: char },
+}
+
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum YamlParseError {
+    InvalidYaml { message: String },
+    MissingField { field: String },
+    /// the symbol is not a single character, is the wildcard `*`, or is `_` in a machine which uses the space
+    InvalidSymbol { symbol: String },
+    /// the state is not a scalar, or its name clashes with another one
+    InvalidState { state: String },
+    /// the state is missing from the table
+    UnknownState { state: String },
+    /// the instruction is malformed, or the state has several instructions for the symbol
+    InvalidInstruction { state: String, symbol: char },
+}
+
+impl YamlMachine {
+    /// the machine starting in `state` on the tape. The ruleset must be single-tape and deterministic.
+    pub fn new(ruleset: Ruleset, state: impl Into<RuleState>, tape: Tape) -> Result<YamlMachine, YamlError> {
+        if ruleset.tapes() != 1 {
+            return Err(YamlError::MultiTape { tapes: ruleset.tapes() });
+        }
+        for state in ruleset.states().iter().filter(|state| !ruleset.is_halting_state(state)) {
+            for symbol in symbols(&ruleset, &tape) {
+                if ruleset.find_all(state, &[symbol]).len() > 1 {
+                    return Err(YamlError::Nondeterministic { state: state.clone(), symbol });
+                }
+            }
+        }
+        Ok(YamlMachine { ruleset, state: state.into(), tape })
+    }
+
+    pub fn ruleset(&self) -> &Ruleset {
+        &self.ruleset
+    }
+
+    pub fn state(&self) -> RuleState {
+        self.state.clone()
+    }
+
+    pub fn tape(&self) -> &Tape {
+        &self.tape
+    }
+
+    pub fn into_turing(self) -> Turing {
+        Turing::new(self.tape, self.state, self.ruleset)
+    }
+}
+
+/// the symbols the machine may read: the alphabet, the blank, the written symbols and the symbols on the tape
+fn symbols(ruleset: &Ruleset, tape: &Tape) -> Vec<char> {
+    let mut symbols = Vec::new();
+    let writes = ruleset.states().iter().flat_map(|state| ruleset.symbols().iter().flat_map(move |row| ruleset.cell(state, row)));
+    let cells: Vec<char> = tape.runs().into_iter().flat_map(|(_, cells)| cells).collect();
+    for symbol in ruleset.alphabet().iter().chain([ruleset.blank()].iter()).chain(writes.map(|rule| &rule.writes[0])).chain(cells.iter()) {
+        if *symbol != WILDCARD && !symbols.contains(symbol) {
+            symbols.push(*symbol);
+        }
+    }
+    symbols
+}
+
+impl Display for YamlMachine {
+    /// writes the machine for turingmachine.io. The input starts at the first non-blank cell of the tape or at the head,
+    /// and the machine first walks right up to the head through `seek` states. A rule which halts the machine after writing or
+    /// changing the state goes right to a `{state}_stop` state, which comes back and enters a state with no instructions.
+    /// The accept and the reject states have no instructions, so the machine halts in them.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let ruleset = &self.ruleset;
+        let symbols = symbols(ruleset, &self.tape);
+        // the rules of every state for the symbols, without the halts which change nothing
+        let mut table: Vec<(RuleState, Vec<(char, Rule)>)> = Vec::new();
+        for state in ruleset.states() {
+            let mut instructions = Vec::new();
+            if !ruleset.is_halting_state(state) {
+                for symbol in &symbols {
+                    let Some(rule) = ruleset.find_all(state, &[*symbol]).first() else {
+                        continue;
+                    };
+                    let write = if rule.write() == WILDCARD { *symbol } else { rule.write() };
+                    if !rule.is_terminal() || write != *symbol || rule.next_state != *state {
+                        instructions.push((*symbol, Rule::new(write, rule.mov(), rule.next_state.clone())));
+                    }
+                }
+            }
+            table.push((state.clone(), instructions));
+        }
+        let mut taken: HashSet<RuleState> = ruleset.states().iter().cloned().collect();
+        taken.extend(table.iter().flat_map(|(_, instructions)| instructions.iter().map(|(_, rule)| rule.next_state.clone())));
+        taken.extend(ruleset.accept_states().iter().chain(ruleset.reject_states()).cloned());
+        taken.insert(self.state.clone());
+        let has_instructions = |state: &RuleState| table.iter().any(|(s, instructions)| s == state && !instructions.is_empty());
+
+        let mut lines: Vec<(RuleState, Instructions)> = Vec::new();
+        let (first, last) = self.tape.content_bounds();
+        let seek: Vec<RuleState> = (first..self.tape.head()).map(|_| unique("seek".to_string(), &mut taken)).collect();
+        for (i, state) in seek.iter().enumerate() {
+            let next = seek.get(i + 1).unwrap_or(&self.state);
+            lines.push((state.clone(), vec![(instruction(None, Move::Right, Some(next)), symbols.clone())]));
+        }
+        let mut stops: Vec<(RuleState, RuleState, RuleState)> = Vec::new();
+        for (state, instructions) in &table {
+            let mut grouped: Instructions = Vec::new();
+            for (symbol, rule) in instructions {
+                let write = (rule.write() != *symbol).then_some(rule.write());
+                let text = if rule.is_terminal() {
+                    let stop = match stops.iter().find(|(target, _, _)| *target == rule.next_state) {
+                        Some((_, stop, _)) => stop.clone(),
+                        None => {
+                            let stop = unique(format!("{}_stop", rule.next_state), &mut taken);
+                            let halted = if has_instructions(&rule.next_state) {
+                                unique(format!("{}_halted", rule.next_state), &mut taken)
+                            } else {
+                                rule.next_state.clone()
+                            };
+                            stops.push((rule.next_state.clone(), stop.clone(), halted));
+                            stop
+                        }
+                    };
+                    instruction(write, Move::Right, Some(&stop))
+                } else {
+                    instruction(write, rule.mov(), (rule.next_state != *state).then_some(&rule.next_state))
+                };
+                match grouped.iter_mut().find(|(t, _)| *t == text) {
+                    Some((_, symbols)) => symbols.push(*symbol),
+                    None => grouped.push((text, vec![*symbol])),
+                }
+            }
+            lines.push((state.clone(), grouped));
+        }
+        for (_, stop, halted) in &stops {
+            lines.push((stop.clone(), vec![(instruction(None, Move::Left, Some(halted)), symbols.clone())]));
+        }
+        // the states which are entered but have no column
+        let targets = table.iter().flat_map(|(_, instructions)| instructions.iter().map(|(_, rule)| &rule.next_state));
+        for state in targets.chain(stops.iter().map(|(_, _, halted)| halted)).chain([&self.state]) {
+            if !lines.iter().any(|(s, _)| s == state) {
+                lines.push((state.clone(), vec![]));
+            }
+        }
+
+        let cells: Vec<char> = (first..=last).map(|position| self.tape.get(position)).collect();
+        let length = cells.iter().rposition(|c| *c != ruleset.blank()).map_or(0, |i| i + 1);
+        writeln!(f, "input: {}", quote(&cells[..length].iter().collect::<String>()))?;
+        writeln!(f, "blank: {}", quote(&ruleset.blank().to_string()))?;
+        writeln!(f, "start state: {}", name(seek.first().unwrap_or(&self.state)))?;
+        writeln!(f, "table:")?;
+        for (state, instructions) in lines {
+            writeln!(f, "  {}:", name(&state))?;
+            for (text, symbols) in instructions {
+                let symbols: Vec<String> = symbols.iter().map(|symbol| quote(&symbol.to_string())).collect();
+                match symbols.as_slice() {
+                    [symbol] => writeln!(f, "    {}: {}", symbol, text)?,
+                    _ => writeln!(f, "    [{}]: {}", symbols.join(", "), text)?,
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// the text of every instruction of a state with the symbols it is written for
+type Instructions = Vec<(String, Vec<char>)>;
+
+/// `L`, `R` or a mapping like `{write: 'a', L: next}`
+fn instruction(write: Option<char>, mov: Move, next: Option<&RuleState>) -> String {
+    let mov = if mov == Move::Left { "L" } else { "R" };
+    match (write, next) {
+        (None, None) => mov.to_string(),
+        _ => {
+            let mut parts = Vec::new();
+            if let Some(write) = write {
+                parts.push(format!("write: {}", quote(&write.to_string())));
+            }
+            match next {
+                Some(next) => parts.push(format!("{}: {}", mov, name(next))),
+                None => parts.push(mov.to_string()),
+            }
+            format!("{{{}}}", parts.join(", "))
+        }
+    }
+}
+
+fn quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "''"))
+}
+
+/// the name of the state, quoted when YAML could read it as a number or a keyword
+fn name(state: &RuleState) -> String {
+    match Yaml::from_str(state.name()) {
+        Yaml::String(_) if state.name().starts_with(char::is_alphabetic) => state.name().to_string(),
+        _ => quote(state.name()),
+    }
+}
+
+impl FromStr for YamlMachine {
+    type Err = YamlParseError;
+
+    /// reads a machine written for turingmachine.io. The start state becomes the first column of the table,
+    /// and the blank, usually a space, is read as `_`. Every state gets a `*` row which halts the machine, for the symbols without instructions.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let documents = YamlLoader::load_from_str(s).map_err(|e| YamlParseError::InvalidYaml { message: e.to_string() })?;
+        let machine = documents.first().and_then(Yaml::as_hash).ok_or_else(|| YamlParseError::InvalidYaml { message: "the machine is not a mapping".to_string() })?;
+        let field = |name: &str| machine.get(&Yaml::String(name.to_string())).filter(|value| !value.is_null());
+        let missing = |field: &str| YamlParseError::MissingField { field: field.to_string() };
+        let mut reader = Symbols::default();
+        let blank = read_symbol(&mut reader, field("blank").ok_or_else(|| missing("blank"))?)?;
+        let table = field("table").and_then(Yaml::as_hash).ok_or_else(|| missing("table"))?;
+
+        let mut names: HashMap<String, RuleState> = HashMap::new();
+        let mut states = Vec::new();
+        for name in table.keys() {
+            let name = scalar(name).ok_or_else(|| YamlParseError::InvalidState { state: format!("{:?}", name) })?;
+            let state = match state_name(&name) {
+                Some(state) if !states.contains(&state) => state,
+                _ => return Err(YamlParseError::InvalidState { state: name }),
+            };
+            names.insert(name, state.clone());
+            states.push(state);
+        }
+        let find = |name: String| names.get(&name).cloned().ok_or(YamlParseError::UnknownState { state: name });
+        let start = find(field("start state").and_then(scalar).ok_or_else(|| missing("start state"))?)?;
+
+        let mut rules: HashMap<RuleState, HashMap<Vec<char>, Vec<Rule>>> = HashMap::new();
+        let mut rows: Vec<Vec<char>> = Vec::new();
+        for ((name, instructions), state) in table.iter().zip(&states) {
+            let cells = rules.entry(state.clone()).or_default();
+            cells.insert(vec![WILDCARD], vec![Rule::new(WILDCARD, Move::Stop, state.clone())]);
+            let instructions = match instructions {
+                Yaml::Null => continue,
+                Yaml::Hash(instructions) => instructions,
+                _ => return Err(YamlParseError::InvalidState { state: scalar(name).unwrap_or_default() }),
+            };
+            for (symbols, instruction) in instructions {
+                let symbols = match symbols {
+                    Yaml::Array(symbols) => symbols.iter().map(|symbol| read_symbol(&mut reader, symbol)).collect::<Result<Vec<char>, _>>()?,
+                    symbol => vec![read_symbol(&mut reader, symbol)?],
+                };
+                for symbol in symbols {
+                    let invalid = || YamlParseError::InvalidInstruction { state: scalar(name).unwrap_or_default(), symbol };
+                    let (write, mov, next) = match instruction {
+                        Yaml::String(mov) => (symbol, direction(mov).ok_or_else(invalid)?, state.clone()),
+                        Yaml::Hash(fields) => {
+                            let mut write = symbol;
+                            let mut target = None;
+                            for (key, value) in fields {
+                                match (key.as_str().and_then(direction), key.as_str()) {
+                                    (Some(mov), _) if target.is_none() => {
+                                        let next = match value {
+                                            Yaml::Null => state.clone(),
+                                            value => find(scalar(value).ok_or_else(invalid)?)?,
+                                        };
+                                        target = Some((mov, next));
+                                    }
+                                    (None, Some("write")) => write = read_symbol(&mut reader, value)?,
+                                    _ => return Err(invalid()),
+                                }
+                            }
+                            let (mov, next) = target.ok_or_else(invalid)?;
+                            (write, mov, next)
+                        }
+                        _ => return Err(invalid()),
+                    };
+                    if cells.insert(vec![symbol], vec![Rule::new(write, mov, next)]).is_some() {
+                        return Err(invalid());
+                    }
+                    if !rows.contains(&vec![symbol]) {
+                        rows.push(vec![symbol]);
+                    }
+                }
+            }
+        }
+        rows.push(vec![WILDCARD]);
+        // the start state goes first
+        states.sort_by_key(|state| *state != start);
+
+        let input = match field("input") {
+            Some(input) => scalar(input).ok_or_else(|| YamlParseError::InvalidSymbol { symbol: format!("{:?}", input) })?,
+            None => String::new(),
+        };
+        let input: Vec<char> = input.chars().map(|c| read_char(&mut reader, c)).collect::<Result<_, _>>()?;
+        if reader.clash() {
+            return Err(YamlParseError::InvalidSymbol { symbol: SPACE.to_string() });
+        }
+        let mut ruleset = Ruleset::new(rules, rows, states);
+        ruleset.set_blank(blank);
+        Ok(YamlMachine { ruleset, state: start, tape: Tape::with_blank(input, 0, 0, blank) })
+    }
+}
+
+fn read_symbol(reader: &mut Symbols, yaml: &Yaml) -> Result<char, YamlParseError> {
+    let text = scalar(yaml).ok_or_else(|| YamlParseError::InvalidSymbol { symbol: format!("{:?}", yaml) })?;
+    let mut chars = text.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => read_char(reader, c),
+        _ => Err(YamlParseError::InvalidSymbol { symbol: text }),
+    }
+}
+
+/// the space is the blank
+fn read_char(reader: &mut Symbols, c: char) -> Result<char, YamlParseError> {
+    match c {
+        ' ' => Ok(reader.blank()),
+        c => reader.symbol(c).ok_or(YamlParseError::InvalidSymbol { symbol: c.to_string() }),
+    }
+}
+
+/// text of a string, a number or a boolean
+fn scalar(yaml: &Yaml) -> Option<String> {
+    match yaml {
+        Yaml::String(s) | Yaml::Real(s) => Some(s.clone()),
+        Yaml::Integer(i) => Some(i.to_string()),
+        Yaml::Boolean(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+fn direction(text: &str) -> Option<Move> {
+    match text {
+        "L" => Some(Move::Left),
+        "R" => Some(Move::Right),
+        _ => None,
+    }
+}
+
+impl Display for YamlError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            YamlError::MultiTape { tapes } => write!(f, "Only single-tape machines can be written to YAML, the ruleset has {} tapes", tapes),
+            YamlError::Nondeterministic { state, symbol } => write!(f, "Several rules for state {} and symbol {}", state, symbol),
+        }
+    }
+}
+
+impl Display for YamlParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            YamlParseError::InvalidYaml { message } => write!(f, "Invalid YAML: {}", message),
+            YamlParseError::MissingField { field } => write!(f, "Missing machine field: {}", field),
+            YamlParseError::InvalidSymbol { symbol } => write!(f, "Invalid symbol: {}", symbol),
+            YamlParseError::InvalidState { state } => write!(f, "Invalid state: {}", state),
+            YamlParseError::UnknownState { state } => write!(f, "State {} is not in the table", state),
+            YamlParseError::InvalidInstruction { state, symbol } => write!(f, "Invalid instruction for state {} and symbol {}", state, symbol),
+        }
+    }
+}
+
+impl Error for YamlError {}
+impl Error for YamlParseError {}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+    use crate::outcome::Outcome;
+    use crate::ruleset::Ruleset;
+    use crate::tape::Tape;
+    use crate::yaml::{YamlError, YamlMachine, YamlParseError};
+
+    const INCREMENT: &str = "\
+input: '1011'
+blank: ' '
+start state: right
+table:
+  # scan to the rightmost digit
+  right:
+    [1, 0]: R
+    ' ': {L: carry}
+  carry:
+    1: {write: 0, L}
+    [0, ' ']: {write: 1, L: done}
+  done:
+";
+
+    #[test]
+    fn test_yaml_from_str() {
+        let machine = YamlMachine::from_str(INCREMENT).unwrap();
+        assert_eq!(machine.ruleset().to_string(), Ruleset::from_str("\
+|   | right | carry | done |
+|:-:|:-:    |:-:    |:-:   |
+| 1 | 1>right | 0<carry |   |
+| 0 | 0>right | 1<done |   |
+| _ | _<carry | 1<done |   |
+| * | *!right | *!carry | *!done |").unwrap().to_string());
+        assert_eq!(machine.tape().data().iter().collect::<String>(), "1011");
+        let mut turing = machine.into_turing();
+        turing.detect_loops(true);
+        assert_eq!(turing.run(100).unwrap().outcome(), &Outcome::Halted);
+        assert_eq!(turing.detected_loop(), None);
+        assert_eq!(turing.state(), "done".parse().unwrap());
+        assert_eq!(turing.tape().to_string(), "[1] 1  0  0  _ ");
+    }
+
+    #[test]
+    fn test_yaml_display() {
+        let ruleset = Ruleset::from_str("\
+|   | 0   | 1   |
+|:-:|:-:  |:-:  |
+| a | a>0 | b!1 |
+| b | a!1 |     |").unwrap();
+        let machine = YamlMachine::new(ruleset, 0, Tape::new("aab".chars().collect(), 1, 0)).unwrap();
+        assert_eq!(machine.to_string(), "\
+input: 'aab'
+blank: '_'
+start state: seek
+table:
+  seek:
+    ['a', 'b', '_']: {R: '0'}
+  '0':
+    'a': R
+    'b': {write: 'a', R: '1_stop'}
+  '1':
+    'a': {write: 'b', R: '1_stop'}
+  '1_stop':
+    ['a', 'b', '_']: {L: '1_halted'}
+  '1_halted':
+");
+        // reads the written machine back, halting at the same cell
+        let mut turing = YamlMachine::from_str(&machine.to_string()).unwrap().into_turing();
+        turing.run(100).unwrap();
+        assert_eq!(turing.state(), "1_halted".parse().unwrap());
+        assert_eq!(turing.tape().to_string(), " a  a [a] _ ");
+
+        // the blanks the head went over are left out
+        let mut tape = Tape::new("ab".chars().collect(), 0, 0);
+        tape.set_head(-1_000_000_000);
+        tape.set_head(1);
+        let text = YamlMachine::new(machine.ruleset().clone(), 0, tape).unwrap().to_string();
+        assert!(text.starts_with("input: 'ab'\nblank: '_'\nstart state: seek\ntable:\n  seek:\n    ['a', 'b', '_']: {R: '0'}\n  '0':"));
+    }
+
+    #[test]
+    fn test_yaml_scalars() {
+        // the input, the states and the symbols may be numbers or booleans, and the names are sanitized
+        let machine = YamlMachine::from_str("\
+input: 10
+blank: ' '
+start state: 1
+table:
+  1:
+    [0, 1]: R
+    ' ': {write: 1, L: go-back}
+  go-back:
+    [0, 1]: L
+    ' ': {R: true}
+  true:
+").unwrap();
+        assert_eq!(machine.state(), 1.into());
+        assert_eq!(machine.ruleset().states(), &vec![1.into(), "go_back".parse().unwrap(), "true".parse().unwrap()]);
+        let text = machine.to_string();
+        // the names which YAML reads as numbers or booleans are quoted
+        assert_eq!(text, "\
+input: '10'
+blank: '_'
+start state: '1'
+table:
+  '1':
+    ['0', '1']: R
+    '_': {write: '1', L: go_back}
+  go_back:
+    ['0', '1']: L
+    '_': {R: 'true'}
+  'true':
+");
+        let mut turing = YamlMachine::from_str(&text).unwrap().into_turing();
+        turing.run(100).unwrap();
+        assert_eq!(turing.state(), "true".parse().unwrap());
+        assert_eq!(turing.tape().to_string(), " _ [1] 0  1 ");
+    }
+
+    #[test]
+    fn test_yaml_errors() {
+        let nondeterministic = Ruleset::from_str("\
+|   | 0       |
+|:-:|:-:      |
+| a | a>0 b!0 |").unwrap();
+        assert_eq!(YamlMachine::new(nondeterministic, 0, Tape::new(vec![], 0, 0)).err(), Some(YamlError::Nondeterministic { state: 0.into(), symbol: 'a' }));
+        let two_tapes = Ruleset::from_str("\
+|    | 0     |
+|:-:|:-:    |
+| a_ | a>a>0 |").unwrap();
+        assert_eq!(YamlMachine::new(two_tapes, 0, Tape::new(vec![], 0, 0)).err(), Some(YamlError::MultiTape { tapes: 2 }));
+        assert_eq!(YamlMachine::from_str("blank: ' '").err(), Some(YamlParseError::MissingField { field: "table".to_string() }));
+        assert_eq!(YamlMachine::from_str(&INCREMENT.replace("L: done", "L: end")).err(), Some(YamlParseError::UnknownState { state: "end".to_string() }));
+        assert_eq!(YamlMachine::from_str(&INCREMENT.replace("{write: 0, L}", "{write: 0}")).err(), Some(YamlParseError::InvalidInstruction { state: "carry".to_string(), symbol: '1' }));
+        assert_eq!(YamlMachine::from_str(&INCREMENT.replace("[1, 0]", "[1, 0, 10]")).err(), Some(YamlParseError::InvalidSymbol { symbol: "10".to_string() }));
+        assert_eq!(YamlMachine::from_str(&INCREMENT.replace("[0, ' ']", "[0, _]")).err(), Some(YamlParseError::InvalidSymbol { symbol: "_".to_string() }));
+        assert_eq!(YamlMachine::from_str(&INCREMENT.replace("[1, 0]", "[1, '*']")).err(), Some(YamlParseError::InvalidSymbol { symbol: "*".to_string() }));
+        assert_eq!(YamlMachine::from_str(&INCREMENT.replace("[1, 0]", "[1, 1]")).err(), Some(YamlParseError::InvalidInstruction { state: "right".to_string(), symbol: '1' }));
+        assert_eq!(YamlMachine::from_str(&INCREMENT.replace("{L: carry}", "{L: carry, R: done}")).err(), Some(YamlParseError::InvalidInstruction { state: "right".to_string(), symbol: '_' }));
+        assert_eq!(YamlMachine::from_str(&format!("{}  don-e:\n", INCREMENT.replace("done:", "don_e:"))).err(), Some(YamlParseError::InvalidState { state: "don-e".to_string() }));
+        assert_eq!(YamlMachine::from_str("- blank").err(), Some(YamlParseError::InvalidYaml { message: "the machine is not a mapping".to_string() }));
+    }
+}